@@ -2,16 +2,63 @@
 //!
 //! Uses blocking HTTP to avoid Tokio runtime conflicts with GPUI.
 
+use std::ops::ControlFlow;
+use std::time::Duration;
+
 use anyhow::Result;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use crate::debug_log;
+
 const DEFAULT_API_URL: &str = "http://localhost:8765";
+pub const DEFAULT_TIMEOUT_SECS: u64 = 120;
+/// Longest `Retry-After` a GET waits out before retrying on its own
+const MAX_AUTO_RETRY_WAIT: Duration = Duration::from_secs(10);
+/// Wait used when a 429 carries no (usable) `Retry-After`
+const DEFAULT_RETRY_WAIT: Duration = Duration::from_secs(2);
+
+/// The backend (or the LLM behind it) answered HTTP 429
+#[derive(Debug, Clone)]
+pub struct RateLimited {
+    pub retry_after: Option<Duration>,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.retry_after {
+            Some(wait) => write!(f, "Rate limited, retry in {}s", wait.as_secs().max(1)),
+            None => write!(f, "Rate limited, retry shortly"),
+        }
+    }
+}
+
+impl std::error::Error for RateLimited {}
 
 /// Chat request payload
 #[derive(Debug, Clone, Serialize)]
 pub struct ChatRequest {
     pub message: String,
     pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Answer this request with another user's memory, without switching
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+}
+
+/// A prior turn replayed into the backend context when branching
+#[derive(Debug, Clone, Serialize)]
+pub struct SeedMessage {
+    pub role: String,
+    pub content: String,
+}
+
+/// Payload for seeding a branched session
+#[derive(Debug, Clone, Serialize)]
+pub struct SeedRequest {
+    pub session_id: String,
+    pub messages: Vec<SeedMessage>,
 }
 
 /// API response for chat messages
@@ -32,23 +79,37 @@ pub struct ToolCall {
     pub success: bool,
 }
 
-/// Agent info response
+/// Agent info response. Everything but provider and model is optional, so a
+/// backend with a slightly different shape still connects.
 #[derive(Debug, Clone, Deserialize)]
 pub struct AgentInfo {
     pub provider: String,
     pub model: String,
+    #[serde(default)]
     pub skills: Vec<String>,
+    #[serde(default)]
     pub tools: Vec<String>,
+    #[serde(default)]
     pub memory_enabled: bool,
+    #[serde(default)]
     pub knowledge_enabled: bool,
+    /// Backend build version (older backends don't report it)
+    #[serde(default)]
+    pub version: Option<String>,
 }
 
 /// Memory item from BIEM
+///
+/// Only the text is required: in the `context` stream event, one item missing
+/// a field would otherwise fail the whole event, emptying both sidebar panels.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryItem {
+    #[serde(default)]
     pub id: String,
     pub content: String,
+    #[serde(default)]
     pub energy: f32,
+    #[serde(default)]
     pub tier: String,
 }
 
@@ -62,14 +123,17 @@ pub struct MemoryContextResponse {
     pub error: Option<String>,
 }
 
-/// Knowledge triple
+/// Knowledge triple (the triple itself is required)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KnowledgeTriple {
+    #[serde(default)]
     pub id: String,
     pub subject: String,
     pub predicate: String,
     pub object: String,
+    #[serde(default)]
     pub confidence: f32,
+    #[serde(default)]
     pub source: String,
 }
 
@@ -84,7 +148,7 @@ pub struct KnowledgeTriplesResponse {
 }
 
 /// Memory stats response
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct MemoryStats {
     #[serde(default)]
     pub l1_count: i64,
@@ -101,7 +165,7 @@ pub struct MemoryStats {
 }
 
 /// Knowledge stats response
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct KnowledgeStats {
     #[serde(default)]
     pub total_triples: i64,
@@ -117,6 +181,12 @@ pub struct KnowledgeStats {
     pub status: Option<String>,
 }
 
+/// Available LLM providers response
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ProvidersResponse {
+    pub providers: Vec<String>,
+}
+
 /// Users list response
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct UsersResponse {
@@ -128,27 +198,36 @@ pub struct UsersResponse {
 #[derive(Debug, Clone, Deserialize)]
 pub struct UserSwitchResponse {
     pub success: bool,
+    #[serde(default)]
     pub user_id: String,
+    #[serde(default)]
+    pub error: Option<String>,
 }
 
-/// Context memory item from stream
+/// Tool retry response
 #[derive(Debug, Clone, Deserialize)]
-pub struct ContextMemory {
-    pub id: String,
-    pub content: String,
-    pub energy: f32,
-    pub tier: String,
+pub struct ToolRetryResponse {
+    pub result: String,
 }
 
-/// Context knowledge item from stream
+/// Memory used for the current query, from the `context` stream event. The
+/// backend sends the same shape as /api/memory/context, so "Load more" pages
+/// drop straight into the panel.
+pub type ContextMemory = MemoryItem;
+
+/// Knowledge used for the current query, from the `context` stream event; the
+/// same shape as /api/knowledge/triples rows
+pub type ContextKnowledge = KnowledgeTriple;
+
+/// A knowledge triple added or changed while answering
 #[derive(Debug, Clone, Deserialize)]
-pub struct ContextKnowledge {
-    pub id: String,
+pub struct KnowledgeChange {
     pub subject: String,
     pub predicate: String,
     pub object: String,
-    pub confidence: f32,
-    pub source: String,
+    /// Previous object, for updated triples
+    #[serde(default)]
+    pub old_object: Option<String>,
 }
 
 /// Stream event types from SSE
@@ -161,9 +240,15 @@ pub enum StreamEvent {
         memories: Vec<ContextMemory>,
         #[serde(default)]
         knowledge: Vec<ContextKnowledge>,
+        /// Most triples the backend would send; a full list means more may exist
+        #[serde(default)]
+        knowledge_limit: Option<usize>,
     },
     #[serde(rename = "chunk")]
     Chunk { content: String },
+    /// Reasoning tokens, kept apart from the answer text
+    #[serde(rename = "thinking")]
+    Thinking { content: String },
     #[serde(rename = "tool_start")]
     ToolStart {
         name: String,
@@ -179,13 +264,54 @@ pub enum StreamEvent {
         #[serde(default)]
         result: String,
     },
+    /// Source backing the answer text streamed so far
+    #[serde(rename = "citation")]
+    Citation {
+        #[serde(default)]
+        id: String,
+        #[serde(default)]
+        source: String,
+        #[serde(default)]
+        title: String,
+        #[serde(default)]
+        url: Option<String>,
+    },
+    /// Triples learned from the exchange; arrives after `done`
+    #[serde(rename = "knowledge_update")]
+    KnowledgeUpdate {
+        #[serde(default)]
+        added: Vec<KnowledgeChange>,
+        #[serde(default)]
+        updated: Vec<KnowledgeChange>,
+    },
     #[serde(rename = "done")]
     Done {
         #[serde(default)]
         tool_calls: Vec<StreamToolCall>,
     },
     #[serde(rename = "error")]
-    Error { content: String },
+    Error {
+        content: String,
+        /// Error category from the backend; see `StreamEvent::is_recoverable_error`
+        #[serde(default)]
+        kind: Option<String>,
+    },
+}
+
+impl StreamEvent {
+    /// Recoverable errors (e.g. a failed tool) end the turn but keep the partial
+    /// reply; anything else, including errors without a kind, is fatal.
+    pub fn is_recoverable_error(kind: Option<&str>) -> bool {
+        matches!(kind, Some("recoverable" | "tool" | "tool_error" | "rate_limit"))
+    }
+}
+
+/// What a finished stream produced besides its events
+#[derive(Debug, Clone, Default)]
+pub struct StreamOutcome {
+    pub tool_calls: Vec<ToolCall>,
+    /// Event types (or "unparseable") of data the GUI could not decode
+    pub dropped_events: Vec<String>,
 }
 
 /// Tool call from stream
@@ -200,49 +326,119 @@ pub struct StreamToolCall {
 #[derive(Clone)]
 pub struct ApiClient {
     base_url: String,
+    timeout_secs: u64,
 }
 
 impl ApiClient {
     pub fn new(base_url: Option<String>) -> Self {
         Self {
             base_url: base_url.unwrap_or_else(|| DEFAULT_API_URL.to_string()),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
         }
     }
 
+    /// Set the request timeout in seconds
+    pub fn with_timeout(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
     pub fn get_base_url(&self) -> &str {
         &self.base_url
     }
 
+    pub fn get_timeout_secs(&self) -> u64 {
+        self.timeout_secs
+    }
+
     fn client(&self) -> reqwest::blocking::Client {
         reqwest::blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(120))
+            .timeout(std::time::Duration::from_secs(self.timeout_secs))
             .build()
             .unwrap_or_else(|_| reqwest::blocking::Client::new())
     }
 
     /// Send a chat message and get response (blocking)
-    pub fn chat(&self, message: &str, session_id: Option<String>) -> Result<ChatResponse> {
+    pub fn chat(
+        &self,
+        message: &str,
+        session_id: Option<String>,
+        system_prompt: Option<String>,
+    ) -> Result<ChatResponse> {
         let url = format!("{}/api/chat", self.base_url);
         let request = ChatRequest {
             message: message.to_string(),
             session_id,
+            system_prompt,
+            user_id: None,
         };
 
-        let response = self
-            .client()
-            .post(&url)
-            .json(&request)
-            .send()?
-            .json::<ChatResponse>()?;
+        let response = send(self.client().post(&url).json(&request))?;
+        parse_json(response)
+    }
 
-        Ok(response)
+    /// GET that waits out a short rate limit once before giving up (blocking)
+    fn get_with_retry(&self, url: &str) -> Result<reqwest::blocking::Response> {
+        let response = send(self.client().get(url))?;
+        let Some(limited) = rate_limit(&response) else {
+            return Ok(response);
+        };
+        let wait = limited.retry_after.unwrap_or(DEFAULT_RETRY_WAIT);
+        if wait > MAX_AUTO_RETRY_WAIT {
+            return Err(limited.into());
+        }
+        debug_log::log(|| format!("   rate limited, retrying {} in {:?}", url, wait));
+        std::thread::sleep(wait);
+        send(self.client().get(url))
     }
 
     /// Get agent information (blocking)
     pub fn get_agent_info(&self) -> Result<AgentInfo> {
         let url = format!("{}/api/agent/info", self.base_url);
-        let response = self.client().get(&url).send()?.json()?;
-        Ok(response)
+        let response = self.get_with_retry(&url)?;
+        parse_json(response)
+    }
+
+    /// Switch the backend LLM model (blocking)
+    pub fn set_model(&self, model: &str) -> Result<AgentInfo> {
+        let url = format!("{}/api/agent/model?model={}", self.base_url, urlencoding::encode(model));
+        let response = send(self.client().post(&url))?;
+        parse_json(response)
+    }
+
+    /// List the LLM providers the backend can switch to (blocking)
+    pub fn get_providers(&self) -> Result<Vec<String>> {
+        let url = format!("{}/api/agent/providers", self.base_url);
+        let response = send(self.client().get(&url))?;
+        let providers: ProvidersResponse = parse_json(response)?;
+        Ok(providers.providers)
+    }
+
+    /// Switch the LLM provider; the backend may also reset the model (blocking)
+    pub fn set_provider(&self, provider: &str) -> Result<AgentInfo> {
+        let url = format!(
+            "{}/api/agent/provider?provider={}",
+            self.base_url,
+            urlencoding::encode(provider)
+        );
+        let response = send(self.client().post(&url))?;
+        parse_json(response)
+    }
+
+    /// Download an image from an arbitrary URL, returning its content type and bytes (blocking)
+    pub fn fetch_image(&self, url: &str) -> Result<(String, Vec<u8>)> {
+        let response = send(self.client().get(url))?;
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("HTTP {} fetching {}", status, url);
+        }
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        Ok((content_type, response.bytes()?.to_vec()))
     }
 
     /// Get memory context for a query (blocking, user-specific)
@@ -254,50 +450,74 @@ impl ApiClient {
             limit,
             urlencoding::encode(user_id)
         );
-        let response = self.client().get(&url).send()?.json()?;
-        Ok(response)
+        let response = send(self.client().get(&url))?;
+        parse_json(response)
     }
 
     /// Get memory statistics (blocking, user-specific)
     pub fn get_memory_stats(&self, user_id: &str) -> Result<MemoryStats> {
         let url = format!("{}/api/stats?user_id={}", self.base_url, urlencoding::encode(user_id));
-        let response = self.client().get(&url).send()?.json()?;
-        Ok(response)
+        let response = self.get_with_retry(&url)?;
+        parse_json(response)
     }
 
     /// Get knowledge triples (blocking)
     pub fn get_knowledge_triples(&self, limit: usize) -> Result<KnowledgeTriplesResponse> {
         let url = format!("{}/api/knowledge/triples?limit={}", self.base_url, limit);
-        let response = self.client().get(&url).send()?.json()?;
-        Ok(response)
+        let response = send(self.client().get(&url))?;
+        parse_json(response)
+    }
+
+    /// Search knowledge triples relevant to a query (blocking)
+    pub fn search_knowledge(&self, query: &str, limit: usize) -> Result<KnowledgeTriplesResponse> {
+        let url = format!(
+            "{}/api/knowledge/search?q={}&limit={}",
+            self.base_url,
+            urlencoding::encode(query),
+            limit
+        );
+        let response = send(self.client().get(&url))?;
+        parse_json(response)
     }
 
     /// Get knowledge statistics (blocking)
     pub fn get_knowledge_stats(&self) -> Result<KnowledgeStats> {
         let url = format!("{}/api/knowledge/stats", self.base_url);
-        let response = self.client().get(&url).send()?.json()?;
-        Ok(response)
+        let response = self.get_with_retry(&url)?;
+        parse_json(response)
     }
 
     /// Get list of users (blocking)
     pub fn get_users(&self) -> Result<UsersResponse> {
         let url = format!("{}/api/users", self.base_url);
-        let response = self.client().get(&url).send()?.json()?;
-        Ok(response)
+        let response = send(self.client().get(&url))?;
+        parse_json(response)
     }
 
     /// Switch to a different user (blocking)
     pub fn switch_user(&self, user_id: &str) -> Result<UserSwitchResponse> {
         let url = format!("{}/api/user/switch?user_id={}", self.base_url, urlencoding::encode(user_id));
-        let response = self.client().post(&url).send()?.json()?;
-        Ok(response)
+        let response = send(self.client().post(&url))?;
+        parse_json(response)
     }
 
     /// Create a new user (blocking)
     pub fn create_user(&self, user_id: &str) -> Result<UserSwitchResponse> {
         let url = format!("{}/api/user/create?user_id={}", self.base_url, urlencoding::encode(user_id));
-        let response = self.client().post(&url).send()?.json()?;
-        Ok(response)
+        let response = send(self.client().post(&url))?;
+        parse_json(response)
+    }
+
+    /// Rename a user; the backend rejects names already in use (blocking)
+    pub fn rename_user(&self, old: &str, new: &str) -> Result<UserSwitchResponse> {
+        let url = format!(
+            "{}/api/user/rename?old_user_id={}&new_user_id={}",
+            self.base_url,
+            urlencoding::encode(old),
+            urlencoding::encode(new)
+        );
+        let response = send(self.client().post(&url))?;
+        parse_json(response)
     }
 
     /// Clear conversation (blocking)
@@ -305,55 +525,432 @@ impl ApiClient {
         let url = format!(
             "{}/api/chat/clear?session_id={}",
             self.base_url,
-            session_id.unwrap_or_default()
+            urlencoding::encode(&session_id.unwrap_or_default())
         );
-        self.client().post(&url).send()?;
+        let response = send(self.client().post(&url))?;
+        // Checks the status too, so a backend that refused the clear is an error
+        parse_json::<serde_json::Value>(response)?;
         Ok(())
     }
 
-    /// Stream chat response with callback for each chunk
-    pub fn chat_stream<F>(&self, message: &str, session_id: Option<String>, mut on_event: F) -> Result<Vec<ToolCall>>
-    where
-        F: FnMut(StreamEvent),
-    {
-        use std::io::BufRead;
+    /// Replace the backend conversation context with earlier turns (blocking)
+    pub fn seed_session(&self, session_id: &str, messages: Vec<SeedMessage>) -> Result<()> {
+        let url = format!("{}/api/chat/seed", self.base_url);
+        let request = SeedRequest {
+            session_id: session_id.to_string(),
+            messages,
+        };
+        let response = send(self.client().post(&url).json(&request))?;
+        parse_json::<serde_json::Value>(response)?;
+        Ok(())
+    }
 
+    /// Re-run a tool call from a session (blocking)
+    pub fn retry_tool(&self, session_id: &str, tool_id: &str) -> Result<String> {
         let url = format!(
+            "{}/api/tool/retry?session_id={}&tool_id={}",
+            self.base_url,
+            urlencoding::encode(session_id),
+            urlencoding::encode(tool_id)
+        );
+        let response = send(self.client().post(&url))?;
+        let retry: ToolRetryResponse = parse_json(response)?;
+        Ok(retry.result)
+    }
+
+    /// The GET URL `chat_stream` sends for a request (the payload travels as query params)
+    pub fn chat_stream_url(&self, request: &ChatRequest) -> String {
+        let mut url = format!(
             "{}/api/chat/stream?message={}&session_id={}",
             self.base_url,
-            urlencoding::encode(message),
-            session_id.unwrap_or_default()
+            urlencoding::encode(&request.message),
+            urlencoding::encode(request.session_id.as_deref().unwrap_or_default())
         );
+        if let Some(prompt) = &request.system_prompt {
+            url.push_str(&format!("&system_prompt={}", urlencoding::encode(prompt)));
+        }
+        if let Some(user_id) = &request.user_id {
+            url.push_str(&format!("&user_id={}", urlencoding::encode(user_id)));
+        }
+        url
+    }
+
+    /// Stream chat response with callback for each chunk
+    pub fn chat_stream<F>(&self, request: &ChatRequest, mut on_event: F) -> Result<StreamOutcome>
+    where
+        // Returning Break drops the connection and ends the stream early
+        F: FnMut(StreamEvent) -> ControlFlow<()>,
+    {
+        use std::io::BufRead;
 
-        let response = self.client().get(&url).send()?;
+        let url = self.chat_stream_url(request);
+        let response = send(self.client().get(&url))?;
+        let status = response.status();
+        debug_log::log(|| format!("<- {} {} (stream)", status, response.url()));
+        if let Some(limited) = rate_limit(&response) {
+            return Err(limited.into());
+        }
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            anyhow::bail!("HTTP {}: {}", status, error_detail(&body));
+        }
         let reader = std::io::BufReader::new(response);
 
-        let mut tool_calls = Vec::new();
+        let mut outcome = StreamOutcome::default();
+        let mut event_name: Option<String> = None;
+        let mut data_lines: Vec<String> = Vec::new();
 
-        for line in reader.lines() {
+        // A trailing event without its blank line still counts, hence the extra ""
+        let lines = reader.lines().chain(std::iter::once(Ok(String::new())));
+        for line in lines {
             let line = line?;
-            if line.starts_with("data: ") {
-                let data = &line[6..];
-                if let Ok(event) = serde_json::from_str::<StreamEvent>(data) {
-                    match &event {
-                        StreamEvent::Done { tool_calls: tcs } => {
-                            tool_calls = tcs
-                                .iter()
-                                .map(|tc| ToolCall {
-                                    name: tc.name.clone(),
-                                    arguments: tc.arguments.clone(),
-                                    result: None,
-                                    success: true,
-                                })
-                                .collect();
+
+            // Blank line ends an event (SSE spec)
+            if line.is_empty() {
+                if !data_lines.is_empty() {
+                    let data = data_lines.join("\n");
+                    match decode_stream_event(&data, event_name.as_deref()) {
+                        Ok(event) => {
+                            debug_log::log(|| format!("   event {:?}", event));
+                            if let StreamEvent::Done { tool_calls } = &event {
+                                outcome.tool_calls = tool_calls
+                                    .iter()
+                                    .map(|tc| ToolCall {
+                                        name: tc.name.clone(),
+                                        arguments: tc.arguments.clone(),
+                                        result: None,
+                                        success: true,
+                                    })
+                                    .collect();
+                            }
+                            if on_event(event).is_break() {
+                                return Ok(outcome);
+                            }
+                        }
+                        Err(kind) => {
+                            debug_log::log(|| {
+                                format!("   dropped {} event: {}", kind, debug_log::truncate(&data))
+                            });
+                            outcome.dropped_events.push(kind);
                         }
-                        _ => {}
                     }
-                    on_event(event);
                 }
+                event_name = None;
+                data_lines.clear();
+                continue;
+            }
+
+            let (field, value) = line.split_once(':').unwrap_or((line.as_str(), ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "data" => data_lines.push(value.to_string()),
+                "event" => event_name = Some(value.to_string()),
+                // Comments (": keep-alive"), id and retry fields carry no payload
+                _ => {}
             }
         }
 
-        Ok(tool_calls)
+        Ok(outcome)
+    }
+}
+
+/// Send a request, recording it in the debug log when enabled
+fn send(request: reqwest::blocking::RequestBuilder) -> Result<reqwest::blocking::Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    debug_log::log(|| {
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| format!(" {}", debug_log::truncate(&String::from_utf8_lossy(bytes))))
+            .unwrap_or_default();
+        format!("-> {} {}{}", request.method(), request.url(), body)
+    });
+    Ok(client.execute(request)?)
+}
+
+/// Decode one SSE event's data, using the `event:` name when the JSON has no
+/// `type`. On failure returns the event type (or "unparseable") for reporting.
+fn decode_stream_event(data: &str, event_name: Option<&str>) -> std::result::Result<StreamEvent, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(data).map_err(|_| "unparseable".to_string())?;
+    if let (Some(obj), Some(name)) = (value.as_object_mut(), event_name) {
+        obj.entry("type")
+            .or_insert_with(|| serde_json::Value::String(name.to_string()));
+    }
+    let kind = value
+        .get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or("untyped")
+        .to_string();
+    serde_json::from_value(value).map_err(|_| kind)
+}
+
+/// Read a JSON response, turning error statuses and non-JSON bodies
+/// (e.g. an HTML 500 page) into readable errors
+fn parse_json<T: DeserializeOwned>(response: reqwest::blocking::Response) -> Result<T> {
+    if let Some(limited) = rate_limit(&response) {
+        debug_log::log(|| format!("<- {} {}", response.status(), response.url()));
+        return Err(limited.into());
+    }
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let url = response.url().to_string();
+    let body = response.text()?;
+    debug_log::log(|| format!("<- {} {}: {}", status, url, debug_log::truncate(&body)));
+
+    if !status.is_success() {
+        anyhow::bail!("HTTP {}: {}", status, error_detail(&body));
+    }
+    if content_type.starts_with("text/html") {
+        anyhow::bail!("expected JSON but got {}: {}", content_type, error_detail(&body));
+    }
+    serde_json::from_str(&body).map_err(|e| {
+        anyhow::anyhow!("unexpected response from backend ({}): {}", e, error_detail(&body))
+    })
+}
+
+/// `Some` when the response is an HTTP 429, with its `Retry-After` if given
+fn rate_limit(response: &reqwest::blocking::Response) -> Option<RateLimited> {
+    if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    Some(RateLimited {
+        retry_after: retry_after(response.headers()),
+    })
+}
+
+/// The usable `Retry-After` wait in a response's headers, if any
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_retry_after)
+}
+
+/// `Retry-After` is either delay seconds or an HTTP date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means "now"
+    Some((at.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or_default())
+}
+
+/// Extract a short, human-readable message from an error body
+fn error_detail(body: &str) -> String {
+    // FastAPI reports errors as {"detail": "..."}
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(body) {
+        if let Some(detail) = value.get("detail").and_then(|d| d.as_str()) {
+            return detail.to_string();
+        }
+    }
+
+    // Strip HTML tags and collapse whitespace so error pages stay readable
+    let text = regex::Regex::new(r"<[^>]*>")
+        .map(|re| re.replace_all(body, " ").to_string())
+        .unwrap_or_else(|_| body.to_string());
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return "empty response body".to_string();
+    }
+    if text.chars().count() > 200 {
+        let snippet: String = text.chars().take(200).collect();
+        format!("{}...", snippet)
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decoded query parameters of a URL, in order
+    fn query_params(url: &str) -> Vec<(String, String)> {
+        let (_, query) = url.split_once('?').expect("URL has a query string");
+        query
+            .split('&')
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (
+                    urlencoding::decode(key).unwrap().into_owned(),
+                    urlencoding::decode(value).unwrap().into_owned(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chat_stream_url_round_trips_special_characters() {
+        let client = ApiClient::new(Some("http://localhost:8765".to_string()));
+        let request = ChatRequest {
+            message: "what is 1 + 1 & why? #math 日本語 🎉".to_string(),
+            session_id: Some("session a&b#c=ü".to_string()),
+            system_prompt: Some("Be brief & polite # always, café".to_string()),
+            user_id: Some("zoë & co #1".to_string()),
+        };
+
+        let url = client.chat_stream_url(&request);
+        assert!(url.starts_with("http://localhost:8765/api/chat/stream?"));
+        // Nothing that would split or cut off the query survives unencoded
+        let query = url.split_once('?').unwrap().1;
+        assert!(!query.contains(' ') && !query.contains('#'));
+        assert_eq!(query.matches('&').count(), 3);
+
+        assert_eq!(
+            query_params(&url),
+            vec![
+                ("message".to_string(), request.message.clone()),
+                ("session_id".to_string(), "session a&b#c=ü".to_string()),
+                ("system_prompt".to_string(), "Be brief & polite # always, café".to_string()),
+                ("user_id".to_string(), "zoë & co #1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn chat_stream_url_omits_unset_optional_params() {
+        let client = ApiClient::new(Some("http://localhost:8765".to_string()));
+        let request = ChatRequest {
+            message: "hi".to_string(),
+            session_id: None,
+            system_prompt: None,
+            user_id: None,
+        };
+
+        assert_eq!(
+            query_params(&client.chat_stream_url(&request)),
+            vec![
+                ("message".to_string(), "hi".to_string()),
+                ("session_id".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn agent_info_accepts_minimal_and_older_responses() {
+        let info: AgentInfo =
+            serde_json::from_str(r#"{"provider": "openai", "model": "gpt-4o"}"#).unwrap();
+        assert_eq!(info.provider, "openai");
+        assert_eq!(info.model, "gpt-4o");
+        assert!(info.skills.is_empty() && info.tools.is_empty());
+        assert!(!info.memory_enabled && !info.knowledge_enabled);
+        assert_eq!(info.version, None);
+
+        // An older backend: no version, plus fields this client doesn't know
+        let info: AgentInfo = serde_json::from_str(
+            r#"{
+                "provider": "anthropic",
+                "model": "claude",
+                "skills": ["search"],
+                "tools": ["read_file", "bash"],
+                "memory_enabled": true,
+                "knowledge_enabled": false,
+                "max_turns": 20
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(info.skills, vec!["search"]);
+        assert_eq!(info.tools, vec!["read_file", "bash"]);
+        assert!(info.memory_enabled);
+        assert_eq!(info.version, None);
+    }
+
+    #[test]
+    fn context_event_decodes_sparse_memory_items() {
+        let data = r#"{
+            "type": "context",
+            "memories": [
+                {"content": "Prefers dark mode"},
+                {"id": "m2", "content": "Works on the GUI", "energy": 0.8}
+            ]
+        }"#;
+
+        let Ok(StreamEvent::Context { memories, knowledge, .. }) = decode_stream_event(data, None)
+        else {
+            panic!("context event should decode");
+        };
+        assert!(knowledge.is_empty());
+        assert_eq!(memories.len(), 2);
+        assert_eq!(memories[0].content, "Prefers dark mode");
+        assert_eq!(memories[0].id, "");
+        assert_eq!(memories[0].energy, 0.0);
+        assert_eq!(memories[0].tier, "");
+        assert_eq!(memories[1].id, "m2");
+        assert_eq!(memories[1].energy, 0.8);
+
+        // The type may come from the SSE `event:` line instead
+        let data = r#"{"memories": [{"content": "Prefers dark mode"}]}"#;
+        assert!(matches!(
+            decode_stream_event(data, Some("context")),
+            Ok(StreamEvent::Context { memories, .. }) if memories.len() == 1
+        ));
+    }
+
+    #[test]
+    fn context_event_decodes_sparse_knowledge_triples() {
+        let data = r#"{
+            "type": "context",
+            "knowledge": [
+                {"subject": "Alice", "predicate": "works_at", "object": "Acme"},
+                {"id": "k2", "subject": "Acme", "predicate": "based_in", "object": "Berlin",
+                 "confidence": 0.9, "source": "user"}
+            ]
+        }"#;
+
+        let Ok(StreamEvent::Context { memories, knowledge, .. }) = decode_stream_event(data, None)
+        else {
+            panic!("context event should decode");
+        };
+        assert!(memories.is_empty());
+        assert_eq!(knowledge.len(), 2);
+        assert_eq!(knowledge[0].subject, "Alice");
+        assert_eq!(knowledge[0].object, "Acme");
+        assert_eq!(knowledge[0].id, "");
+        assert_eq!(knowledge[0].confidence, 0.0);
+        assert_eq!(knowledge[0].source, "");
+        assert_eq!(knowledge[1].id, "k2");
+        assert_eq!(knowledge[1].source, "user");
+
+        // A triple without its object is not a triple; the event is reported as dropped
+        let data = r#"{"type": "context", "knowledge": [{"subject": "Alice", "predicate": "works_at"}]}"#;
+        assert_eq!(decode_stream_event(data, None).unwrap_err(), "context");
+    }
+
+    #[test]
+    fn retry_after_reads_delay_seconds() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 5 "), Some(Duration::from_secs(5)));
+        assert_eq!(parse_retry_after("0"), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn retry_after_missing_or_garbage_is_none() {
+        assert_eq!(retry_after(&reqwest::header::HeaderMap::new()), None);
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+        for garbage in ["", "-5", "1.5", "tomorrow", "Wed, 99 Foo 2015 07:28:00 GMT"] {
+            assert_eq!(parse_retry_after(garbage), None, "{:?}", garbage);
+        }
+    }
+
+    #[test]
+    fn retry_after_date_in_the_past_means_now() {
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
     }
 }
@@ -1,11 +1,37 @@
 //! Core App state and initialization for OmniEmployee GUI
 
-use gpui::{div, AppContext as _, Context, Entity, IntoElement, ParentElement, Render, Styled, Window};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use gpui::{
+    actions, div, prelude::FluentBuilder, px, AnimationExt, AnyElement, AppContext as _, Context,
+    ElementId, Entity, FocusHandle, InteractiveElement, IntoElement, KeyBinding, ListAlignment,
+    ListState, MouseButton, ParentElement, Pixels, Point, Render, Styled, Task, Window,
+};
 use gpui_component::input::{InputEvent, InputState as GpuiInputState};
 
-use crate::api::{ApiClient, ContextKnowledge, ContextMemory, KnowledgeTriple, MemoryItem, ToolCall};
-use crate::models::{AppConfig, ChatMessage, ConnectionStatus, LiveToolCall};
-use crate::theme::MonokaiTheme;
+use crate::api::{
+    ApiClient, ChatRequest, ContextKnowledge, ContextMemory, KnowledgeStats, KnowledgeTriple,
+    MemoryItem, MemoryStats, ToolCall,
+};
+use crate::debug_log;
+use crate::icons::Icons;
+use crate::models::{
+    looks_like_tool_error, AppConfig, ChatMessage, ConnectionStatus, KnowledgeSort,
+    KnowledgeToast, LiveToolCall, ToolFilter, ToolImage, ToolScope, ToolStatus,
+};
+use crate::sessions::SessionMeta;
+use crate::theme::{apply_scrollbar_colors, expand_animation, MonokaiTheme};
+
+actions!(app, [DismissOverlays]);
+
+/// Window-wide key bindings
+pub fn bind_app_keys(cx: &mut gpui::App) {
+    cx.bind_keys([KeyBinding::new("escape", DismissOverlays, None)]);
+}
+
+/// Longest wait between automatic reconnect attempts
+const RECONNECT_MAX_DELAY_SECS: u64 = 30;
 
 /// Main application state
 pub struct App {
@@ -13,62 +39,191 @@ pub struct App {
     pub messages: Vec<ChatMessage>,
     pub config: AppConfig,
     pub session_id: String,
+    /// Title and lineage of the current session
+    pub session_meta: SessionMeta,
     pub is_loading: bool,
+    /// Instruction prompt sent with every message in this session
+    pub system_prompt: Option<String>,
+    /// Divider left by a user switch that kept the chat; messages above it
+    /// belong to the previous user and are shown only, never saved
+    pub session_divider_id: Option<String>,
+    /// Last chat request and the URL it was transmitted as (for /lastrequest)
+    pub last_request: Option<(ChatRequest, String)>,
 
     // Input component
     pub input_state: Entity<GpuiInputState>,
 
+    // Settings overlay
+    pub show_settings: bool,
+    pub settings_url_input: Entity<GpuiInputState>,
+    pub settings_timeout_input: Entity<GpuiInputState>,
+
     // API client
     pub api_client: ApiClient,
     pub connection_status: ConnectionStatus,
 
+    // Automatic reconnect after a failed connect (dropping the task cancels it)
+    pub reconnect_attempt: u32,
+    pub reconnect_in_secs: Option<u64>,
+    pub reconnect_task: Option<Task<()>>,
+
+    // In-flight connect attempt; a newer attempt replaces (and cancels) it and
+    // bumps the generation so a late result can't overwrite newer state
+    pub connect_task: Option<Task<()>>,
+    pub connect_generation: u64,
+    /// Splash screen over the first connection attempt (see components/splash.rs)
+    pub show_splash: bool,
+
     // Agent info
     pub agent_model: String,
     pub agent_provider: String,
     pub agent_skills: Vec<String>,
     pub agent_tools: Vec<String>,
+    pub backend_version: Option<String>,
 
     // User management
     pub current_user_id: String,
     pub available_users: Vec<String>,
     pub show_user_dropdown: bool,
+    /// Popover with the full connection error (opened from the status indicator)
+    pub show_connection_error: bool,
+
+    // Knowledge learned from the last reply, shown briefly as a toast
+    pub knowledge_toast: Option<KnowledgeToast>,
+
+    // Backend profiles (see AppConfig::profiles)
+    pub show_profile_dropdown: bool,
+
+    // Right-click menu: target message id and where it was opened
+    pub message_menu: Option<(String, Point<Pixels>)>,
+
+    // Chat view filtering (messages are kept, only skipped when rendering)
+    pub hide_system_messages: bool,
+
+    // Messages currently showing their raw, uncleaned content
+    pub raw_view_messages: HashSet<String>,
 
     // Collapsible panel states
     pub memory_expanded: bool,
     pub knowledge_expanded: bool,
+    pub skills_expanded: bool,
     pub tool_expanded: bool,
+    pub tool_filter: ToolFilter,
 
     // Real data from API (all user memories / global knowledge - for reference)
+    /// All of the user's memories, loaded by the memory browser
     pub memory_items: Vec<MemoryItem>,
     pub knowledge_triples: Vec<KnowledgeTriple>,
     pub current_tool_calls: Vec<ToolCall>,
 
+    // "All memories" browser overlay
+    pub show_memory_browser: bool,
+    pub memory_browser_loading: bool,
+    pub memory_browser_energy_desc: bool,
+
+    /// Recent client-side errors as (timestamp, message), oldest first
+    pub error_log: Vec<(String, String)>,
+    pub show_error_log: bool,
+
     // Live tool calls (real-time updates during streaming)
     pub live_tool_calls: Vec<LiveToolCall>,
+    /// Expanded runs of same-named tool calls, keyed by the run's first call id
+    pub expanded_tool_groups: HashSet<String>,
+    pub tool_scope: ToolScope,
+    /// Expanded rows in the session-wide tool list (independent of live rows)
+    pub session_tool_expanded: HashSet<String>,
+
+    // Images decoded from image tool results, keyed by tool id
+    pub tool_images: HashMap<String, ToolImage>,
+
+    // Failed-tool retry state: in-flight retries and tools the backend can't retry
+    pub retrying_tools: HashSet<String>,
+    pub unretryable_tools: HashMap<String, String>,
+
+    // Live stats strip (refreshed every `config.stats_interval_secs`)
+    pub live_memory_stats: Option<MemoryStats>,
+    pub live_knowledge_stats: Option<KnowledgeStats>,
+    pub stats_task: Option<Task<()>>,
 
     // Context used for current query (what was actually loaded)
     pub current_context_memories: Vec<ContextMemory>,
     pub current_context_knowledge: Vec<ContextKnowledge>,
+    /// How many memories/facts the query actually used ("Load more" grows the panels past this)
+    pub context_memory_count: usize,
+    pub context_knowledge_count: usize,
+    pub knowledge_sort: KnowledgeSort,
+
+    // "Load more" paging for the Memory/Knowledge panels
+    pub last_query: String,
+    pub memory_has_more: bool,
+    pub knowledge_has_more: bool,
+    pub loading_more: bool,
 
     // Streaming state
     pub streaming_content: String,
     pub streaming_message_id: Option<String>,
+    /// When the last stream event arrived (drives the typing indicator)
+    pub last_chunk_at: Option<Instant>,
+    /// When the first chunk of the current reply arrived (drives the rate display)
+    pub stream_started_at: Option<Instant>,
+
+    // Virtualized message list (follows new content unless the user scrolled up)
+    pub message_list: ListState,
+    /// Ids of the messages currently shown as list rows
+    pub message_rows: Vec<String>,
+    pub stick_to_bottom: bool,
+    /// Content streamed in while scrolled up (shows the "New messages" pill)
+    pub unseen_stream_content: bool,
+    /// "Finish & stop": end the reply at the next chunk or tool result once no
+    /// tool is running, keeping what has streamed so far
+    pub stop_after_step: bool,
+
+    // Keyboard navigation (j/k, Up/Down) over list rows
+    pub messages_focus: FocusHandle,
+    pub selected_message: Option<usize>,
+
+    // Replies that finished while the window was in the background (shown in the title)
+    pub window_active: bool,
+    pub unread_count: usize,
+    /// Last title pushed to the window, to avoid resetting it every frame
+    pub window_title: String,
 }
 
 impl App {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
-        let api_client = ApiClient::new(None);
         let session_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
 
-        // Create input state with proper IME support
+        // Create input state with proper IME support; grows up to 6 lines
         let input_state = cx.new(|cx| {
-            GpuiInputState::new(window, cx).placeholder("Type a message... (/ for commands)")
+            GpuiInputState::new(window, cx)
+                .auto_grow(1, 6)
+                .placeholder("Type a message... (/ for commands, Shift+Enter for newline)")
         });
 
-        // Subscribe to input events for Enter key handling
+        let settings_url_input = cx.new(|cx| {
+            GpuiInputState::new(window, cx).placeholder("http://localhost:8765")
+        });
+        let settings_timeout_input =
+            cx.new(|cx| GpuiInputState::new(window, cx).placeholder("Timeout (seconds)"));
+
+        // Subscribe to input events for Enter key handling and the length counter
         cx.subscribe_in(&input_state, window, |this, input_state: &Entity<GpuiInputState>, event: &InputEvent, window, cx| {
+            if let InputEvent::Change { .. } = event {
+                if this.config.max_input_chars > 0 {
+                    cx.notify();
+                }
+            }
             if let InputEvent::PressEnter { .. } = event {
+                // Shift+Enter keeps the newline the multi-line input just inserted
+                if window.modifiers().shift {
+                    return;
+                }
                 let text = input_state.read(cx).value().to_string();
+                // Keep an over-long draft in the input so it can be trimmed
+                if !this.check_input_limit(&text) {
+                    cx.notify();
+                    return;
+                }
                 input_state.update(cx, |state, cx| {
                     state.set_value("", window, cx);
                 });
@@ -77,54 +232,248 @@ impl App {
         })
         .detach();
 
+        // Clear the unread count once the user comes back to the window
+        cx.observe_window_activation(window, |this, window, cx| {
+            this.window_active = window.is_window_active();
+            if this.window_active && this.unread_count > 0 {
+                this.unread_count = 0;
+                cx.notify();
+            }
+        })
+        .detach();
+
+        let config = AppConfig::load();
+        debug_log::init(config.debug);
+
+        // Start on the last used profile, falling back to the default backend URL
+        let profile_url = config
+            .active_profile
+            .as_ref()
+            .and_then(|name| config.profiles.iter().find(|p| &p.name == name))
+            .map(|p| p.url.clone());
+        let api_client = ApiClient::new(profile_url);
+
+        let theme = MonokaiTheme::with_accents(&config.accent_colors);
+        apply_scrollbar_colors(&theme, cx);
+
         Self {
-            theme: MonokaiTheme::new(),
+            theme,
             messages: vec![ChatMessage::system("Connecting to OmniEmployee backend...")],
-            config: AppConfig::default(),
+            config,
             session_id,
+            session_meta: SessionMeta::default(),
             is_loading: false,
+            system_prompt: None,
+            session_divider_id: None,
+            last_request: None,
             input_state,
+            show_settings: false,
+            settings_url_input,
+            settings_timeout_input,
             api_client,
             connection_status: ConnectionStatus::Connecting,
+            reconnect_attempt: 0,
+            reconnect_in_secs: None,
+            reconnect_task: None,
+            connect_task: None,
+            show_splash: true,
+            connect_generation: 0,
             agent_model: String::new(),
             agent_provider: String::new(),
             agent_skills: vec![],
             agent_tools: vec![],
+            backend_version: None,
             current_user_id: String::from("default"),
             available_users: vec![],
             show_user_dropdown: false,
+            show_connection_error: false,
+            show_profile_dropdown: false,
+            knowledge_toast: None,
+            message_menu: None,
+            hide_system_messages: false,
+            raw_view_messages: HashSet::new(),
             memory_expanded: false,
             knowledge_expanded: false,
+            skills_expanded: false,
             tool_expanded: true,
+            tool_filter: ToolFilter::default(),
             memory_items: vec![],
             knowledge_triples: vec![],
+            show_memory_browser: false,
+            memory_browser_loading: false,
+            memory_browser_energy_desc: true,
             current_tool_calls: vec![],
+            error_log: vec![],
+            show_error_log: false,
             live_tool_calls: vec![],
+            expanded_tool_groups: HashSet::new(),
+            tool_scope: ToolScope::default(),
+            session_tool_expanded: HashSet::new(),
+            tool_images: HashMap::new(),
+            retrying_tools: HashSet::new(),
+            unretryable_tools: HashMap::new(),
+            live_memory_stats: None,
+            live_knowledge_stats: None,
+            stats_task: None,
             current_context_memories: vec![],
             current_context_knowledge: vec![],
+            context_memory_count: 0,
+            context_knowledge_count: 0,
+            knowledge_sort: KnowledgeSort::default(),
+            last_query: String::new(),
+            memory_has_more: false,
+            knowledge_has_more: false,
+            loading_more: false,
             streaming_content: String::new(),
             streaming_message_id: None,
+            last_chunk_at: None,
+            stream_started_at: None,
+            message_list: ListState::new(0, ListAlignment::Bottom, px(1024.)),
+            message_rows: vec![],
+            messages_focus: cx.focus_handle(),
+            selected_message: None,
+            stick_to_bottom: true,
+            unseen_stream_content: false,
+            stop_after_step: false,
+            window_active: true,
+            unread_count: 0,
+            window_title: String::new(),
         }
     }
 
+    /// Emoji icons, or their ASCII stand-ins when `ascii_mode` is on
+    pub fn icons(&self) -> &'static Icons {
+        Icons::get(self.config.ascii_mode)
+    }
+
+    /// Wrap content that just expanded so it fades in, growing up to
+    /// `max_height` when given; plain content when `reduce_motion` is set.
+    /// The animation replays each time the content reappears.
+    pub fn expand_transition(
+        &self,
+        id: impl Into<ElementId>,
+        content: impl IntoElement,
+        max_height: Option<f32>,
+    ) -> AnyElement {
+        let wrapper = div().w_full().overflow_hidden().child(content);
+        if self.config.reduce_motion {
+            return wrapper.into_any_element();
+        }
+        wrapper
+            .with_animation(id, expand_animation(), move |el, delta| {
+                let el = el.opacity(delta);
+                match max_height {
+                    Some(height) => el.max_h(px(height * delta)),
+                    None => el,
+                }
+            })
+            .into_any_element()
+    }
+
+    /// A tool that "completed" with what looks like an error as its result
+    pub fn tool_result_suspect(&self, status: &ToolStatus, result: Option<&str>) -> bool {
+        self.config.detect_tool_errors
+            && *status == ToolStatus::Completed
+            && result.is_some_and(looks_like_tool_error)
+    }
+
+    /// Close the topmost open menu, popover or overlay (Escape)
+    pub fn dismiss_overlays(
+        &mut self,
+        _: &DismissOverlays,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.message_menu.is_some() {
+            self.message_menu = None;
+        } else if self.show_connection_error {
+            self.show_connection_error = false;
+        } else if self.show_user_dropdown {
+            self.show_user_dropdown = false;
+        } else if self.show_profile_dropdown {
+            self.show_profile_dropdown = false;
+        } else if self.show_error_log {
+            self.show_error_log = false;
+        } else if self.show_memory_browser {
+            self.show_memory_browser = false;
+        } else if self.show_settings {
+            self.show_settings = false;
+        } else if self.knowledge_toast.is_some() {
+            self.knowledge_toast = None;
+        } else {
+            // Nothing to close; let Escape reach other handlers
+            cx.propagate();
+            return;
+        }
+        cx.notify();
+    }
+
+    /// Point the client at a (possibly different) backend and re-initialize
+    pub fn connect_to_backend(&mut self, url: String, timeout_secs: u64, cx: &mut Context<Self>) {
+        self.api_client = ApiClient::new(Some(url.clone())).with_timeout(timeout_secs);
+        self.cancel_reconnect();
+        self.messages.clear();
+        self.messages
+            .push(ChatMessage::system(format!("Reconnecting to {}...", url)));
+        self.connection_status = ConnectionStatus::Connecting;
+        self.initialize(cx);
+    }
+
+    /// Switch to a saved backend profile and remember it for the next launch
+    pub fn switch_profile(&mut self, name: &str, cx: &mut Context<Self>) {
+        self.show_profile_dropdown = false;
+        let Some(profile) = self.config.profiles.iter().find(|p| p.name == name).cloned() else {
+            self.messages.push(ChatMessage::system(format!(
+                "Unknown profile: {}. Use /profile to list profiles.",
+                name
+            )));
+            cx.notify();
+            return;
+        };
+
+        self.config.active_profile = Some(profile.name.clone());
+        self.save_config();
+        let timeout_secs = self.api_client.get_timeout_secs();
+        self.connect_to_backend(profile.url, timeout_secs, cx);
+        cx.notify();
+    }
+
     pub fn initialize(&mut self, cx: &mut Context<Self>) {
         // Connect to API
+        self.connect_generation += 1;
+        let generation = self.connect_generation;
         let api_client = self.api_client.clone();
-        cx.spawn(async move |this, cx| {
-            let result = cx
-                .background_spawn(async move { api_client.get_agent_info() })
-                .await;
+        let timeout_secs = api_client.get_timeout_secs();
+        self.connect_task = Some(cx.spawn(async move |this, cx| {
+            // Don't rely on the HTTP client alone: give up once the configured timeout passes
+            let request = cx.background_spawn(async move { api_client.get_agent_info() });
+            let result = smol::future::or(request, async move {
+                smol::Timer::after(Duration::from_secs(timeout_secs)).await;
+                Err(anyhow::anyhow!("no response within {}s", timeout_secs))
+            })
+            .await;
 
             match result {
                 Ok(info) => {
                     let _ = this.update(cx, |app, cx| {
+                        if app.connect_generation != generation {
+                            return;
+                        }
                         app.connection_status = ConnectionStatus::Connected;
+                        app.show_splash = false;
+                        app.cancel_reconnect();
                         app.agent_model = info.model;
                         app.agent_provider = info.provider;
                         app.agent_skills = info.skills;
                         app.agent_tools = info.tools;
-                        app.config.show_memory = info.memory_enabled;
-                        app.config.show_knowledge = info.knowledge_enabled;
+                        app.backend_version = info.version;
+                        // Backend defaults only apply until the user sets them
+                        if !app.config.is_explicit("show_memory") {
+                            app.config.show_memory = info.memory_enabled;
+                        }
+                        if !app.config.is_explicit("show_knowledge") {
+                            app.config.show_knowledge = info.knowledge_enabled;
+                        }
 
                         app.messages.clear();
                         app.messages.push(ChatMessage::system(format!(
@@ -146,27 +495,37 @@ impl App {
                                 app.agent_tools.join(", ")
                             }
                         )));
+                        app.restart_stats_polling(cx);
                         cx.notify();
                     });
                 }
                 Err(e) => {
                     let _ = this.update(cx, |app, cx| {
+                        if app.connect_generation != generation {
+                            return;
+                        }
                         app.connection_status =
                             ConnectionStatus::Error(format!("Failed to connect: {}", e));
+                        app.show_splash = false;
+                        app.log_error(format!(
+                            "Could not connect to {}: {}",
+                            app.api_client.get_base_url(),
+                            e
+                        ));
                         app.messages.clear();
                         app.messages.push(ChatMessage::system(format!(
                             "⚠️ Could not connect to backend at {}.\n\n\
                             Make sure the server is running:\n\
                               uv run uvicorn src.omniemployee.web.app:app --port 8765\n\n\
-                            Use /reconnect to try again.",
+                            Retrying automatically, or use /reconnect to try now.",
                             app.api_client.get_base_url()
                         )));
+                        app.schedule_reconnect(cx);
                         cx.notify();
                     });
                 }
             }
-        })
-        .detach();
+        }));
 
         // Load users list
         self.refresh_users(cx);
@@ -175,6 +534,42 @@ impl App {
         self.refresh_sidebar_data(cx);
     }
 
+    /// Retry the connection after a backoff, counting down once a second
+    pub fn schedule_reconnect(&mut self, cx: &mut Context<Self>) {
+        self.reconnect_attempt += 1;
+        let attempt = self.reconnect_attempt;
+        let delay_secs = (1u64 << attempt.min(5)).min(RECONNECT_MAX_DELAY_SECS);
+
+        self.reconnect_task = Some(cx.spawn(async move |this, cx| {
+            for remaining in (1..=delay_secs).rev() {
+                if this
+                    .update(cx, |app, cx| {
+                        app.reconnect_in_secs = Some(remaining);
+                        cx.notify();
+                    })
+                    .is_err()
+                {
+                    return;
+                }
+                smol::Timer::after(std::time::Duration::from_secs(1)).await;
+            }
+
+            let _ = this.update(cx, |app, cx| {
+                app.reconnect_in_secs = None;
+                app.connection_status = ConnectionStatus::Connecting;
+                app.initialize(cx);
+                cx.notify();
+            });
+        }));
+    }
+
+    /// Stop any pending automatic reconnect and reset the attempt counter
+    pub fn cancel_reconnect(&mut self) {
+        self.reconnect_task = None;
+        self.reconnect_in_secs = None;
+        self.reconnect_attempt = 0;
+    }
+
     pub fn refresh_users(&mut self, cx: &mut Context<Self>) {
         let api_client = self.api_client.clone();
         cx.spawn(async move |this, cx| {
@@ -204,13 +599,9 @@ impl App {
                     let _ = this.update(cx, |app, cx| {
                         app.current_user_id = response.user_id;
                         app.show_user_dropdown = false;
-                        app.session_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
-                        app.messages.clear();
-                        app.messages.push(ChatMessage::system(format!(
-                            "Switched to user: {}\nNew session started.",
-                            app.current_user_id
-                        )));
+                        app.start_user_session(format!("Switched to user: {}", app.current_user_id));
                         app.refresh_sidebar_data(cx);
+                        app.restart_stats_polling(cx);
                         cx.notify();
                     });
                 }
@@ -219,6 +610,37 @@ impl App {
         .detach();
     }
 
+    /// Start a fresh session after a user change, clearing the view unless
+    /// `clear_on_user_switch` is off (then a divider marks the switch)
+    fn start_user_session(&mut self, notice: String) {
+        self.session_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
+        self.session_meta = SessionMeta::default();
+        self.system_prompt = None;
+        if self.config.clear_on_user_switch {
+            self.messages.clear();
+            self.messages.push(ChatMessage::system(format!(
+                "{}\nNew session started.",
+                notice
+            )));
+        } else {
+            let divider = ChatMessage::system(format!(
+                "──────── {} • new session ────────",
+                notice
+            ));
+            self.session_divider_id = Some(divider.id.clone());
+            self.messages.push(divider);
+        }
+    }
+
+    /// Index of the first message of the current session: just past the
+    /// user-switch divider, or 0 once the divider is gone (cleared, loaded)
+    pub fn session_start(&self) -> usize {
+        self.session_divider_id
+            .as_ref()
+            .and_then(|id| self.messages.iter().position(|m| &m.id == id))
+            .map_or(0, |index| index + 1)
+    }
+
     pub fn refresh_sidebar_data(&mut self, _cx: &mut Context<Self>) {
         // Context data (memory/knowledge) is now loaded from stream events
         // when a query is sent, showing only what was used for that query.
@@ -226,6 +648,43 @@ impl App {
         // loads all memory/knowledge items.
     }
 
+    /// (Re)start the live stats task; dropping the old task cancels it
+    pub fn restart_stats_polling(&mut self, cx: &mut Context<Self>) {
+        self.stats_task = None;
+        self.live_memory_stats = None;
+        self.live_knowledge_stats = None;
+
+        let interval_secs = self.config.stats_interval_secs;
+        if interval_secs == 0 {
+            cx.notify();
+            return;
+        }
+
+        let api_client = self.api_client.clone();
+        let user_id = self.current_user_id.clone();
+        self.stats_task = Some(cx.spawn(async move |this, cx| loop {
+            let client = api_client.clone();
+            let user = user_id.clone();
+            let (memory, knowledge) = cx
+                .background_spawn(async move {
+                    (client.get_memory_stats(&user), client.get_knowledge_stats())
+                })
+                .await;
+
+            if this
+                .update(cx, |app, cx| {
+                    app.live_memory_stats = memory.ok();
+                    app.live_knowledge_stats = knowledge.ok();
+                    cx.notify();
+                })
+                .is_err()
+            {
+                break;
+            }
+            smol::Timer::after(std::time::Duration::from_secs(interval_secs)).await;
+        }));
+    }
+
     pub fn handle_create_new_user(&mut self, cx: &mut Context<Self>) {
         let new_user_id = format!("user_{}", &uuid::Uuid::new_v4().to_string()[..8]);
         let api_client = self.api_client.clone();
@@ -243,13 +702,12 @@ impl App {
                         if !app.available_users.contains(&response.user_id) {
                             app.available_users.push(response.user_id.clone());
                         }
-                        app.session_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
-                        app.messages.clear();
-                        app.messages.push(ChatMessage::system(format!(
-                            "Created and switched to new user: {}\nNew session started.",
+                        app.start_user_session(format!(
+                            "Created and switched to new user: {}",
                             response.user_id
-                        )));
+                        ));
                         app.refresh_sidebar_data(cx);
+                        app.restart_stats_polling(cx);
                         cx.notify();
                     });
                 }
@@ -261,13 +719,40 @@ impl App {
 
 impl Render for App {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        self.sync_message_list();
+        let mut title = format!(
+            "OmniEmployee — {} ({})",
+            self.current_user_id,
+            self.connection_status.label().trim_end_matches("...")
+        );
+        if self.unread_count > 0 {
+            title = format!("({}) {}", self.unread_count, title);
+        }
+        if self.window_title != title {
+            window.set_window_title(&title);
+            self.window_title = title;
+        }
+        if self.show_splash {
+            return self.render_splash(cx).into_any_element();
+        }
         let theme = &self.theme;
 
         div()
+            .relative()
             .size_full()
             .bg(theme.background)
             .text_color(theme.foreground)
             .flex()
+            .on_action(cx.listener(Self::resend_last_message))
+            .on_action(cx.listener(Self::dismiss_overlays))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|this, _event, _window, cx| {
+                    if this.message_menu.take().is_some() {
+                        cx.notify();
+                    }
+                }),
+            )
             .child(
                 div()
                     .flex_1()
@@ -278,5 +763,16 @@ impl Render for App {
                     .child(self.render_input(window, cx)),
             )
             .child(self.render_sidebar(cx))
+            .when_some(self.message_menu.clone(), |el, (msg_id, position)| {
+                el.child(self.render_message_menu(msg_id, position, cx))
+            })
+            .when(self.show_memory_browser, |el| {
+                el.child(self.render_memory_browser(cx))
+            })
+            .when(self.show_error_log, |el| el.child(self.render_error_log(cx)))
+            .when_some(self.knowledge_toast.as_ref(), |el, toast| {
+                el.child(self.render_knowledge_toast(toast, cx))
+            })
+            .into_any_element()
     }
 }
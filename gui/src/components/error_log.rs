@@ -0,0 +1,76 @@
+//! Error log viewer for OmniEmployee GUI
+//!
+//! Errors also appear as chat messages, but those scroll away; this keeps the
+//! recent ones in one place (opened with /errors).
+
+use gpui::{
+    div, prelude::FluentBuilder, px, Context, InteractiveElement, IntoElement, ParentElement,
+    SharedString, StatefulInteractiveElement, Styled,
+};
+
+use crate::app::App;
+
+impl App {
+    pub fn render_error_log(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+
+        let clear = div()
+            .id("error-log-clear")
+            .px_2()
+            .py_px()
+            .rounded(px(4.))
+            .cursor_pointer()
+            .text_xs()
+            .text_color(theme.foreground_dim)
+            .hover(|s| s.bg(theme.background_highlight))
+            .on_click(cx.listener(|this, _event, _window, cx| {
+                this.error_log.clear();
+                cx.notify();
+            }))
+            .child("Clear");
+
+        let list = div()
+            .when(self.error_log.is_empty(), |el| {
+                el.child(
+                    div()
+                        .px_3()
+                        .py_2()
+                        .text_sm()
+                        .text_color(theme.foreground_muted)
+                        .child("No errors recorded"),
+                )
+            })
+            // Newest first
+            .children(self.error_log.iter().rev().enumerate().map(|(i, (timestamp, message))| {
+                div()
+                    .id(SharedString::from(format!("error-log-{}", i)))
+                    .px_3()
+                    .py_2()
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.foreground_muted)
+                            .child(timestamp.clone()),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(theme.foreground)
+                            .child(message.clone()),
+                    )
+            }));
+
+        self.render_modal(
+            "error-log",
+            format!("Errors ({})", self.error_log.len()),
+            theme.accent_red,
+            px(640.),
+            clear,
+            list,
+            |this| this.show_error_log = false,
+            cx,
+        )
+    }
+}
@@ -1,13 +1,16 @@
 //! Header component for OmniEmployee GUI
 
 use gpui::{
-    anchored, deferred, div, prelude::FluentBuilder, px, Context, FontWeight, Hsla,
-    InteractiveElement, IntoElement, ParentElement, SharedString, StatefulInteractiveElement,
-    Styled,
+    anchored, deferred, div, prelude::FluentBuilder, px, AnimationExt, Context, FontWeight,
+    Hsla, InteractiveElement, IntoElement, ParentElement, SharedString,
+    StatefulInteractiveElement, Styled,
 };
 
+use gpui_component::tooltip::Tooltip;
+
 use crate::app::App;
-use crate::models::ConnectionStatus;
+use crate::models::{ConnectionStatus, Density};
+use crate::theme::pulse_animation;
 
 impl App {
     pub fn render_header(&self, cx: &Context<Self>) -> impl IntoElement {
@@ -20,11 +23,16 @@ impl App {
             ConnectionStatus::Error(_) => theme.accent_red,
         };
 
-        let status_text = match &self.connection_status {
-            ConnectionStatus::Connected => "Connected",
-            ConnectionStatus::Connecting => "Connecting...",
-            ConnectionStatus::Disconnected => "Disconnected",
-            ConnectionStatus::Error(_) => "Error",
+        let status_text = self.connection_status.label();
+
+        let subtitle = if self.agent_model.is_empty() {
+            "AI Assistant".to_string()
+        } else {
+            let mut subtitle = format!("{} • {}", self.agent_model, self.agent_provider);
+            if let Some(version) = &self.backend_version {
+                subtitle.push_str(&format!(" • v{}", version));
+            }
+            subtitle
         };
 
         div()
@@ -42,7 +50,7 @@ impl App {
                     .flex()
                     .items_center()
                     .gap_3()
-                    .child(div().text_xl().child("🤖"))
+                    .child(div().text_xl().child(self.icons().bot))
                     .child(
                         div()
                             .child(
@@ -54,13 +62,16 @@ impl App {
                             )
                             .child(
                                 div()
-                                    .text_xs()
-                                    .text_color(theme.foreground_muted)
-                                    .child(if self.agent_model.is_empty() {
-                                        "AI Assistant".to_string()
-                                    } else {
-                                        format!("{} • {}", self.agent_model, self.agent_provider)
-                                    }),
+                                    .flex()
+                                    .items_center()
+                                    .gap_2()
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(theme.foreground_muted)
+                                            .child(subtitle),
+                                    )
+                                    .child(self.render_session_badge(cx)),
                             ),
                     ),
             )
@@ -69,11 +80,133 @@ impl App {
                     .flex()
                     .items_center()
                     .gap_4()
+                    .when(!self.config.profiles.is_empty(), |el| {
+                        el.child(self.render_profile_selector(cx))
+                    })
                     .child(self.render_user_selector(cx))
-                    .child(self.render_status_indicator(status_text, status_color))
+                    .child(self.render_status_indicator(status_text, status_color, cx))
                     .child(self.render_status_dot("Memory", self.config.show_memory))
-                    .child(self.render_status_dot("Knowledge", self.config.show_knowledge)),
+                    .child(self.render_status_dot("Knowledge", self.config.show_knowledge))
+                    .child(self.render_system_filter_toggle(cx))
+                    .child(self.render_density_toggle(cx))
+                    .child(self.render_settings_button(cx)),
+            )
+    }
+
+    pub fn render_system_filter_toggle(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+        let visible = !self.hide_system_messages;
+
+        div()
+            .id("system-filter-toggle")
+            .flex()
+            .items_center()
+            .gap_1()
+            .cursor_pointer()
+            .on_click(cx.listener(|this, _event, _window, cx| {
+                this.hide_system_messages = !this.hide_system_messages;
+                cx.notify();
+            }))
+            .child(
+                div()
+                    .w_2()
+                    .h_2()
+                    .rounded_full()
+                    .bg(if visible {
+                        theme.accent_yellow
+                    } else {
+                        theme.foreground_muted
+                    }),
             )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(theme.foreground_dim)
+                    .child(if visible { "System" } else { "System (hidden)" }),
+            )
+    }
+
+    pub fn render_density_toggle(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+        let compact = self.config.density == Density::Compact;
+
+        div()
+            .id("density-toggle")
+            .px_2()
+            .py_1()
+            .rounded(px(6.))
+            .cursor_pointer()
+            .text_xs()
+            .text_color(theme.foreground_dim)
+            .hover(|s| s.bg(theme.background_elevated))
+            .tooltip(|window, cx| Tooltip::new("Toggle message density").build(window, cx))
+            .on_click(cx.listener(|this, _event, _window, cx| {
+                this.config.density = this.config.density.toggled();
+                this.config.mark_explicit("density");
+                this.save_config();
+                cx.notify();
+            }))
+            .child(if compact { "Compact" } else { "Comfortable" })
+    }
+
+    pub fn render_settings_button(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+
+        div()
+            .id("settings")
+            .child(
+                div()
+                    .id("settings-button")
+                    .px_2()
+                    .py_1()
+                    .rounded(px(6.))
+                    .cursor_pointer()
+                    .text_sm()
+                    .text_color(if self.show_settings {
+                        theme.accent_cyan
+                    } else {
+                        theme.foreground_dim
+                    })
+                    .hover(|s| s.bg(theme.background_elevated))
+                    .on_click(cx.listener(|this, _event, window, cx| {
+                        this.toggle_settings(window, cx);
+                    }))
+                    .child(self.icons().settings),
+            )
+            .when(self.show_settings, |this| {
+                this.child(self.render_settings_panel(cx))
+            })
+    }
+
+    /// Session title and id badge; click to copy the id for correlating with
+    /// backend logs
+    pub fn render_session_badge(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+        let session_id = self.session_id.clone();
+        let id_label = if session_id.chars().count() > 8 {
+            let short: String = session_id.chars().take(8).collect();
+            format!("#{}…", short)
+        } else {
+            format!("#{}", session_id)
+        };
+        let label = match &self.session_meta.title {
+            Some(title) => format!("{} · {}", title, id_label),
+            None => id_label,
+        };
+
+        div()
+            .id("session-badge")
+            .px_1()
+            .rounded(px(4.))
+            .cursor_pointer()
+            .text_xs()
+            .text_color(theme.foreground_muted)
+            .hover(|s| s.bg(theme.background_elevated).text_color(theme.accent_cyan))
+            .tooltip(|window, cx| Tooltip::new("Click to copy session ID").build(window, cx))
+            .on_click(cx.listener(move |this, _event, _window, cx| {
+                this.copy_to_clipboard(session_id.clone(), cx);
+            }))
+            .child(label)
     }
 
     pub fn render_user_selector(&self, cx: &Context<Self>) -> impl IntoElement {
@@ -106,8 +239,9 @@ impl App {
                         div()
                             .text_xs()
                             .text_color(theme.foreground_muted)
-                            .child("👤"),
+                            .child(self.icons().user),
                     )
+                    .child(self.render_user_color_dot(&current_user))
                     .child(
                         div()
                             .text_sm()
@@ -155,9 +289,13 @@ impl App {
                                     theme.background_elevated
                                 })
                                 .hover(|s| s.bg(theme.background_highlight))
+                                .flex()
+                                .items_center()
+                                .gap_2()
                                 .on_click(cx.listener(move |this, _event, _window, cx| {
                                     this.switch_user(user_clone.clone(), cx);
                                 }))
+                                .child(self.render_user_color_dot(&user))
                                 .child(SharedString::from(user))
                         }))
                         .child(div().h(px(1.)).w_full().bg(theme.border))
@@ -180,25 +318,214 @@ impl App {
             })
     }
 
-    pub fn render_status_indicator(&self, label: &'static str, color: Hsla) -> impl IntoElement {
+    /// Backend profile dropdown, shown once at least one profile is saved
+    pub fn render_profile_selector(&self, cx: &Context<Self>) -> impl IntoElement {
         let theme = &self.theme;
+        let active = self.config.active_profile.clone();
+        let show_dropdown = self.show_profile_dropdown;
+        let profiles = self.config.profiles.clone();
 
         div()
-            .flex()
-            .items_center()
-            .gap_1()
+            .id("profile-selector")
             .child(
                 div()
-                    .w_2()
-                    .h_2()
-                    .rounded_full()
-                    .bg(color)
-                    .when(
-                        self.connection_status == ConnectionStatus::Connecting,
-                        |el| el.bg(color.opacity(0.6)),
+                    .id("profile-button")
+                    .px_3()
+                    .py_1()
+                    .rounded(px(6.))
+                    .bg(theme.background)
+                    .border_1()
+                    .border_color(theme.border)
+                    .cursor_pointer()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .hover(|s| s.bg(theme.background_elevated))
+                    .tooltip(|window, cx| Tooltip::new("Backend profile").build(window, cx))
+                    .on_click(cx.listener(|this, _event, _window, cx| {
+                        this.show_profile_dropdown = !this.show_profile_dropdown;
+                        cx.notify();
+                    }))
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.foreground_muted)
+                            .child(self.icons().backend),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(theme.foreground)
+                            .child(SharedString::from(
+                                active.clone().unwrap_or_else(|| "default".to_string()),
+                            )),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.foreground_muted)
+                            .child(if show_dropdown { "▲" } else { "▼" }),
                     ),
             )
-            .child(div().text_xs().text_color(theme.foreground_dim).child(label))
+            .when(show_dropdown, |this| {
+                this.child(deferred(anchored().child(
+                    div()
+                        .id("profile-dropdown-menu")
+                        .occlude()
+                        .w(px(260.))
+                        .bg(theme.background_elevated)
+                        .border_1()
+                        .border_color(theme.border)
+                        .rounded(px(6.))
+                        .shadow_lg()
+                        .flex()
+                        .flex_col()
+                        .children(profiles.into_iter().map(|profile| {
+                            let name = profile.name.clone();
+                            let is_current = active.as_deref() == Some(profile.name.as_str());
+                            div()
+                                .id(SharedString::from(format!("profile-{}", profile.name)))
+                                .w_full()
+                                .px_3()
+                                .py_2()
+                                .cursor_pointer()
+                                .bg(if is_current {
+                                    theme.background_highlight
+                                } else {
+                                    theme.background_elevated
+                                })
+                                .hover(|s| s.bg(theme.background_highlight))
+                                .on_click(cx.listener(move |this, _event, _window, cx| {
+                                    this.switch_profile(&name, cx);
+                                }))
+                                .child(
+                                    div()
+                                        .text_sm()
+                                        .text_color(if is_current {
+                                            theme.accent_cyan
+                                        } else {
+                                            theme.foreground
+                                        })
+                                        .child(SharedString::from(profile.name)),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(theme.foreground_muted)
+                                        .child(SharedString::from(profile.url)),
+                                )
+                        })),
+                )))
+            })
+    }
+
+    /// Small dot in the user's stable color (see `MonokaiTheme::user_color`)
+    fn render_user_color_dot(&self, user: &str) -> impl IntoElement {
+        div()
+            .flex_none()
+            .w_2()
+            .h_2()
+            .rounded_full()
+            .bg(self.theme.user_color(user))
+    }
+
+    /// Connection status; when it's an error, hover shows the full message and
+    /// a click opens it in a popover with a copy button
+    pub fn render_status_indicator(
+        &self,
+        label: &'static str,
+        color: Hsla,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = &self.theme;
+        let error = match &self.connection_status {
+            ConnectionStatus::Error(error) => Some(error.clone()),
+            _ => None,
+        };
+
+        let dot = div().w_2().h_2().rounded_full().bg(color);
+        let dot = if self.connection_status == ConnectionStatus::Connecting {
+            dot.with_animation("status-pulse", pulse_animation(), |el, delta| {
+                el.opacity(delta)
+            })
+            .into_any_element()
+        } else {
+            dot.into_any_element()
+        };
+
+        let indicator = div()
+            .id("connection-status")
+            .flex()
+            .items_center()
+            .gap_1()
+            .child(dot)
+            .child(div().text_xs().text_color(theme.foreground_dim).child(label));
+
+        let Some(error) = error else {
+            return indicator;
+        };
+        let tooltip_text = error.clone();
+        let copy_text = error.clone();
+
+        indicator
+            .cursor_pointer()
+            .tooltip(move |window, cx| Tooltip::new(tooltip_text.clone()).build(window, cx))
+            .on_click(cx.listener(|this, _event, _window, cx| {
+                this.show_connection_error = !this.show_connection_error;
+                cx.notify();
+            }))
+            .when(self.show_connection_error, |el| {
+                el.child(deferred(anchored().child(
+                    div()
+                        .id("connection-error-popover")
+                        .occlude()
+                        .mt_6()
+                        .w(px(360.))
+                        .p_3()
+                        .bg(theme.background_elevated)
+                        .border_1()
+                        .border_color(theme.border)
+                        .rounded(px(6.))
+                        .shadow_lg()
+                        .flex()
+                        .flex_col()
+                        .gap_2()
+                        .on_click(|_event, _window, cx| cx.stop_propagation())
+                        .child(
+                            div()
+                                .text_xs()
+                                .font_weight(FontWeight::SEMIBOLD)
+                                .text_color(theme.accent_red)
+                                .child("Connection error"),
+                        )
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(theme.foreground)
+                                .whitespace_normal()
+                                .child(error),
+                        )
+                        .child(
+                            div().flex().justify_end().child(
+                                div()
+                                    .id("connection-error-copy")
+                                    .px_2()
+                                    .py_px()
+                                    .rounded(px(4.))
+                                    .cursor_pointer()
+                                    .text_xs()
+                                    .text_color(theme.accent_cyan)
+                                    .hover(|s| s.bg(theme.background_highlight))
+                                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                                        this.copy_to_clipboard(copy_text.clone(), cx);
+                                        this.show_connection_error = false;
+                                        cx.notify();
+                                    }))
+                                    .child("Copy"),
+                            ),
+                        ),
+                )))
+            })
     }
 
     pub fn render_status_dot(&self, label: &'static str, active: bool) -> impl IntoElement {
@@ -1,13 +1,22 @@
 //! Input component for OmniEmployee GUI
 
+use std::path::{Path, PathBuf};
+
 use gpui::{
-    div, prelude::FluentBuilder, ClickEvent, Context, FontWeight, InteractiveElement, IntoElement,
-    ParentElement, StatefulInteractiveElement, Styled, Window,
+    div, prelude::FluentBuilder, ClickEvent, Context, ExternalPaths, FontWeight,
+    InteractiveElement, IntoElement, ParentElement, StatefulInteractiveElement, Styled, Window,
 };
 use gpui_component::input::Input;
+use gpui_component::tooltip::Tooltip;
 
 use crate::app::App;
-use crate::models::ConnectionStatus;
+use crate::models::{ChatMessage, ConnectionStatus};
+
+/// Largest file that can be dropped into the input
+const MAX_DROP_FILE_BYTES: u64 = 256 * 1024;
+
+/// Fraction of `max_input_chars` at which the counter starts warning
+const INPUT_SOFT_LIMIT_RATIO: f32 = 0.8;
 
 impl App {
     pub fn render_input(&self, _window: &Window, cx: &Context<Self>) -> impl IntoElement {
@@ -16,21 +25,27 @@ impl App {
         let is_connected = self.connection_status == ConnectionStatus::Connected;
 
         div()
+            .id("input-area")
             .w_full()
             .p_3()
             .bg(theme.background_secondary)
             .border_t_1()
             .border_color(theme.border)
+            .drag_over::<ExternalPaths>(|style, _, _, _| style.bg(theme.background_highlight))
+            .on_drop(cx.listener(|this, paths: &ExternalPaths, window, cx| {
+                this.handle_file_drop(paths.paths(), window, cx);
+            }))
             .child(
                 div()
                     .flex()
-                    .items_center()
+                    .items_end()
                     .gap_2()
                     .child(
                         div().flex_1().child(
                             Input::new(&self.input_state).appearance(false), // Remove default styling
                         ),
                     )
+                    .when(is_loading, |el| el.child(self.render_stop_button(cx)))
                     .child(
                         div()
                             .id("send-btn")
@@ -56,12 +71,103 @@ impl App {
             .child(
                 div()
                     .mt_1()
+                    .flex()
+                    .justify_between()
                     .text_xs()
                     .text_color(theme.foreground_muted)
-                    .child("Tip: /stats, /memory, /knowledge, /help, /reconnect"),
+                    .child("Tip: Shift+Enter for newline • drop a text file to attach it • /help for commands")
+                    .when_some(self.render_input_counter(cx), |el, counter| el.child(counter)),
             )
     }
 
+    /// "Finish & stop": let the running tool finish, then end the reply
+    fn render_stop_button(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+        let requested = self.stop_after_step;
+
+        div()
+            .id("stop-btn")
+            .px_3()
+            .py_2()
+            .rounded_lg()
+            .border_1()
+            .border_color(theme.border)
+            .text_sm()
+            .text_color(if requested {
+                theme.foreground_muted
+            } else {
+                theme.accent_orange
+            })
+            .when(!requested, |el| {
+                el.cursor_pointer()
+                    .hover(|style| style.bg(theme.background_highlight))
+                    .tooltip(|window, cx| {
+                        Tooltip::new("Let the current tool finish, then stop and keep the partial answer")
+                            .build(window, cx)
+                    })
+                    .on_click(cx.listener(|this, _event, _window, cx| {
+                        this.stop_after_step = true;
+                        cx.notify();
+                    }))
+            })
+            .child(if requested { "Stopping…" } else { "Finish & stop" })
+    }
+
+    /// Live "chars / limit" counter; yellow past the soft threshold, red over the cap
+    fn render_input_counter(&self, cx: &Context<Self>) -> Option<impl IntoElement> {
+        let theme = &self.theme;
+        let limit = self.config.max_input_chars;
+        if limit == 0 {
+            return None;
+        }
+
+        let len = self.input_state.read(cx).value().chars().count();
+        let color = if len > limit {
+            theme.accent_red
+        } else if len as f32 > limit as f32 * INPUT_SOFT_LIMIT_RATIO {
+            theme.accent_yellow
+        } else {
+            theme.foreground_muted
+        };
+        Some(div().text_color(color).child(format!("{} / {}", len, limit)))
+    }
+
+    /// Append dropped text files to the input as fenced code blocks
+    pub fn handle_file_drop(
+        &mut self,
+        paths: &[PathBuf],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut attachments = Vec::new();
+        for path in paths {
+            match read_text_file(path) {
+                Ok(attachment) => attachments.push(attachment),
+                Err(reason) => self.report_error(format!(
+                    "⚠️ Could not attach {}: {}",
+                    path.display(),
+                    reason
+                )),
+            }
+        }
+
+        if !attachments.is_empty() {
+            let current = self.input_state.read(cx).value().to_string();
+            let mut value = current.trim_end().to_string();
+            for attachment in attachments {
+                if !value.is_empty() {
+                    value.push_str("\n\n");
+                }
+                value.push_str(&attachment);
+            }
+            self.input_state.update(cx, |state, cx| {
+                state.set_value(value, window, cx);
+                state.focus(window, cx);
+            });
+        }
+        cx.notify();
+    }
+
     pub fn handle_send_click(
         &mut self,
         _event: &ClickEvent,
@@ -69,9 +175,58 @@ impl App {
         cx: &mut Context<Self>,
     ) {
         let text = self.input_state.read(cx).value().to_string();
+        if !self.check_input_limit(&text) {
+            cx.notify();
+            return;
+        }
         self.input_state.update(cx, |state, cx| {
             state.set_value("", window, cx);
         });
         self.send_message_with_text(text, cx);
     }
+
+    /// Reject input over `max_input_chars`, explaining why; true if it may be sent
+    pub fn check_input_limit(&mut self, text: &str) -> bool {
+        let limit = self.config.max_input_chars;
+        let len = text.trim().chars().count();
+        if limit == 0 || len <= limit {
+            return true;
+        }
+        self.messages.push(ChatMessage::system(format!(
+            "⚠️ Message is {} characters; the limit is {}. Shorten it or raise `max_input_chars`.",
+            len, limit
+        )));
+        false
+    }
+}
+
+/// Read a dropped file as a fenced block, rejecting large or binary files
+fn read_text_file(path: &Path) -> Result<String, String> {
+    let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
+    if metadata.is_dir() {
+        return Err("folders can't be attached".to_string());
+    }
+    if metadata.len() > MAX_DROP_FILE_BYTES {
+        return Err(format!(
+            "file is {} KB (limit {} KB)",
+            metadata.len() / 1024,
+            MAX_DROP_FILE_BYTES / 1024
+        ));
+    }
+
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let text = match String::from_utf8(bytes) {
+        Ok(text) if !text.contains('\0') => text,
+        _ => return Err("looks like a binary file".to_string()),
+    };
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let language = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok(format!("`{}`\n```{}\n{}\n```", name, language, text.trim_end()))
 }
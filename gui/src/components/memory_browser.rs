@@ -0,0 +1,135 @@
+//! "All memories" browser for OmniEmployee GUI
+//!
+//! Unlike the sidebar Memory panel (only what the last query used), this lists
+//! the user's whole working memory.
+
+use gpui::{
+    div, prelude::FluentBuilder, px, Context, InteractiveElement, IntoElement, ParentElement,
+    SharedString, StatefulInteractiveElement, Styled,
+};
+
+use crate::app::App;
+
+/// Memories fetched when the browser opens
+const MEMORY_BROWSER_LIMIT: usize = 100;
+
+impl App {
+    pub fn render_memory_browser(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+
+        let mut items = self.memory_items.clone();
+        items.sort_by(|a, b| {
+            let order = a.energy.total_cmp(&b.energy);
+            if self.memory_browser_energy_desc {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+
+        let sort = div()
+            .id("memory-browser-sort")
+            .px_2()
+            .py_px()
+            .rounded(px(4.))
+            .cursor_pointer()
+            .text_xs()
+            .text_color(theme.foreground_dim)
+            .hover(|s| s.bg(theme.background_highlight))
+            .on_click(cx.listener(|this, _event, _window, cx| {
+                this.memory_browser_energy_desc = !this.memory_browser_energy_desc;
+                cx.notify();
+            }))
+            .child(if self.memory_browser_energy_desc {
+                "Energy ↓"
+            } else {
+                "Energy ↑"
+            });
+
+        let title = format!("{} All Memories ({})", self.icons().memory, items.len());
+        let list = div()
+            .when(self.memory_browser_loading, |el| {
+                el.child(
+                    div()
+                        .px_3()
+                        .py_2()
+                        .text_sm()
+                        .text_color(theme.foreground_muted)
+                        .child("Loading..."),
+                )
+            })
+            .when(!self.memory_browser_loading && items.is_empty(), |el| {
+                el.child(
+                    div()
+                        .px_3()
+                        .py_2()
+                        .text_sm()
+                        .text_color(theme.foreground_muted)
+                        .child("No memories stored for this user"),
+                )
+            })
+            .children(items.into_iter().map(|item| {
+                div()
+                    .id(SharedString::from(format!("memory-browser-{}", item.id)))
+                    .px_3()
+                    .py_2()
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(theme.foreground)
+                            .child(item.content),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.foreground_dim)
+                            .child(format!("E={:.2} • {}", item.energy, item.tier)),
+                    )
+            }));
+
+        self.render_modal(
+            "memory-browser",
+            title,
+            theme.accent_purple,
+            px(560.),
+            sort,
+            list,
+            |this| this.show_memory_browser = false,
+            cx,
+        )
+    }
+
+    /// Open the browser and (re)load the user's memories
+    pub fn open_memory_browser(&mut self, cx: &mut Context<Self>) {
+        self.show_memory_browser = true;
+        self.memory_browser_loading = true;
+        cx.notify();
+
+        let api_client = self.api_client.clone();
+        let user_id = self.current_user_id.clone();
+        cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_spawn(async move {
+                    api_client.get_memory_context("", MEMORY_BROWSER_LIMIT, &user_id)
+                })
+                .await;
+
+            let _ = this.update(cx, |app, cx| {
+                app.memory_browser_loading = false;
+                match result {
+                    Ok(response) => app.memory_items = response.items,
+                    Err(e) => {
+                        app.memory_items.clear();
+                        let text = format!("⚠️ Could not load memories: {}", e);
+                        app.log_error(text.clone());
+                        app.show_transient_message(text, cx);
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+}
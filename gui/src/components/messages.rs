@@ -1,216 +1,1780 @@
 //! Messages component for OmniEmployee GUI
 
+use std::sync::Arc;
+
 use gpui::{
-    div, prelude::FluentBuilder, px, Context, FontWeight, InteractiveElement, IntoElement,
-    ParentElement, SharedString, StatefulInteractiveElement, Styled,
+    actions, anchored, deferred, div, img, list, prelude::FluentBuilder, px, AnimationExt,
+    AnyElement, ClipboardItem, Context, FontWeight, Image, InteractiveElement, IntoElement,
+    KeyBinding, ListOffset, MouseButton, MouseDownEvent, ObjectFit, ParentElement, Pixels, Point,
+    ScrollWheelEvent, SharedString, StatefulInteractiveElement, Styled, StyledImage, Window,
 };
+use gpui_component::scroll::ScrollableElement;
 use gpui_component::text::TextView;
+use gpui_component::tooltip::Tooltip;
+use gpui_component::ActiveTheme;
 
+use crate::api::SeedMessage;
 use crate::app::App;
-use crate::models::{MessageRole, MessageSegment, ToolStatus};
+use crate::models::{
+    format_duration, format_timestamp, one_line_preview, split_code_blocks, superscript_number,
+    truncate_chars, ChatMessage, ContentBlock, Density, ImageReference, MessageRole,
+    MessageSegment, ToolImage, ToolStatus, EMPTY_RESPONSE_NOTE, MAX_MESSAGE_WIDTH,
+    MIN_MESSAGE_WIDTH,
+};
+use crate::theme::pulse_animation;
+
+actions!(
+    messages,
+    [
+        SelectNextMessage,
+        SelectPreviousMessage,
+        CopySelectedMessage,
+        ClearMessageSelection,
+        ResendLastMessage
+    ]
+);
+
+/// Key bindings for moving between messages (active while the list has focus),
+/// plus Cmd/Ctrl+R to resend the last message from anywhere
+pub fn bind_message_keys(cx: &mut gpui::App) {
+    cx.bind_keys([
+        KeyBinding::new("j", SelectNextMessage, Some(MESSAGE_LIST_CONTEXT)),
+        KeyBinding::new("down", SelectNextMessage, Some(MESSAGE_LIST_CONTEXT)),
+        KeyBinding::new("k", SelectPreviousMessage, Some(MESSAGE_LIST_CONTEXT)),
+        KeyBinding::new("up", SelectPreviousMessage, Some(MESSAGE_LIST_CONTEXT)),
+        KeyBinding::new("enter", CopySelectedMessage, Some(MESSAGE_LIST_CONTEXT)),
+        // Text selected inside a message view is copied by the view itself;
+        // with nothing selected this falls through to the whole message
+        KeyBinding::new("secondary-c", CopySelectedMessage, Some(MESSAGE_LIST_CONTEXT)),
+        KeyBinding::new("escape", ClearMessageSelection, Some(MESSAGE_LIST_CONTEXT)),
+        KeyBinding::new("secondary-r", ResendLastMessage, None),
+    ]);
+}
+
+const MESSAGE_LIST_CONTEXT: &str = "MessageList";
+
+/// Characters of a collapsed message shown beside its role
+const COLLAPSED_PREVIEW_CHARS: usize = 80;
+
+/// Starter prompts offered before the first message
+const SUGGESTED_PROMPTS: [&str; 4] = [
+    "What do you remember about me?",
+    "Summarize what you know about my projects",
+    "What tools and skills do you have?",
+    "Help me plan my week",
+];
+
+const EMPTY_STATE_COMMANDS: [(&str, &str); 6] = [
+    ("/help", "All commands and config keys"),
+    ("/memory", "Memory statistics"),
+    ("/knowledge", "Learned knowledge triples"),
+    ("/model <name>", "Switch the LLM model"),
+    ("/history", "Saved sessions"),
+    ("/clear", "Start over"),
+];
 
 impl App {
     pub fn render_messages(&self, cx: &Context<Self>) -> impl IntoElement {
         let theme = &self.theme;
 
-        let message_elements: Vec<_> = self
+        // Only rows near the viewport are rendered; off-screen messages never
+        // build their markdown views
+        let message_list = list(
+            self.message_list.clone(),
+            cx.processor(|this, row: usize, _window, cx| this.render_message_row(row, cx)),
+        )
+        .flex_1()
+        .bg(theme.background);
+
+        div()
+            .flex_1()
+            .relative()
+            .flex()
+            .flex_col()
+            .when_some(self.reconnect_in_secs, |el, secs| {
+                el.child(
+                    div()
+                        .w_full()
+                        .px_4()
+                        .py_1()
+                        .bg(theme.background_secondary)
+                        .border_b_1()
+                        .border_color(theme.border)
+                        .text_xs()
+                        .text_color(theme.accent_yellow)
+                        .child(format!(
+                            "Retrying in {}s (attempt {})...",
+                            secs, self.reconnect_attempt
+                        )),
+                )
+            })
+            .when_some(self.context_warning(), |el, tokens| {
+                el.child(
+                    div()
+                        .w_full()
+                        .px_4()
+                        .py_1()
+                        .bg(theme.background_secondary)
+                        .border_b_1()
+                        .border_color(theme.border)
+                        .text_xs()
+                        .text_color(theme.accent_orange)
+                        .child(format!(
+                            "⚠️ This conversation is ~{} tokens and may exceed the model's context. \
+                            Consider /clear to start fresh.",
+                            tokens
+                        )),
+                )
+            })
+            .when_some(self.system_prompt.clone(), |el, prompt| {
+                el.child(self.render_system_prompt_banner(prompt, cx))
+            })
+            .child(
+                div()
+                    .id("messages-container")
+                    .key_context(MESSAGE_LIST_CONTEXT)
+                    .track_focus(&self.messages_focus)
+                    .flex_1()
+                    .flex()
+                    .flex_col()
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|this, _event, window, _cx| {
+                            window.focus(&this.messages_focus);
+                        }),
+                    )
+                    .on_action(cx.listener(Self::select_next_message))
+                    .on_action(cx.listener(Self::select_previous_message))
+                    .on_action(cx.listener(Self::copy_selected_message))
+                    .on_action(cx.listener(Self::clear_message_selection))
+                    .on_scroll_wheel(cx.listener(Self::handle_messages_scroll))
+                    .child(message_list)
+                    .when(
+                        !self.messages.iter().any(|m| m.role == MessageRole::User),
+                        |el| el.child(self.render_empty_state(cx)),
+                    ),
+            )
+            .when(!self.stick_to_bottom && self.unseen_stream_content, |el| {
+                el.child(
+                    div()
+                        .absolute()
+                        .bottom_3()
+                        .left_0()
+                        .right_0()
+                        .flex()
+                        .justify_center()
+                        .child(
+                            div()
+                                .id("new-messages-pill")
+                                .px_3()
+                                .py_1()
+                                .rounded_full()
+                                .cursor_pointer()
+                                .bg(theme.accent_cyan)
+                                .shadow_lg()
+                                .text_xs()
+                                .font_weight(FontWeight::MEDIUM)
+                                .text_color(theme.background)
+                                .hover(|s| s.opacity(0.85))
+                                .on_click(cx.listener(|this, _event, _window, cx| {
+                                    this.scroll_messages_to_bottom(cx);
+                                }))
+                                .child("↓ New messages"),
+                        ),
+                )
+            })
+            .when(!self.stick_to_bottom && !self.unseen_stream_content, |el| {
+                el.child(
+                    div()
+                        .id("scroll-to-bottom")
+                        .absolute()
+                        .bottom_3()
+                        .right_4()
+                        .size_8()
+                        .rounded_full()
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .cursor_pointer()
+                        .bg(theme.background_elevated)
+                        .border_1()
+                        .border_color(theme.border)
+                        .shadow_lg()
+                        .text_sm()
+                        .text_color(theme.accent_cyan)
+                        .hover(|s| s.bg(theme.background_highlight))
+                        .on_click(cx.listener(|this, _event, _window, cx| {
+                            this.scroll_messages_to_bottom(cx);
+                        }))
+                        .child("↓"),
+                )
+            })
+    }
+
+    /// Estimated token count when it's past the configured warning threshold
+    fn context_warning(&self) -> Option<usize> {
+        let threshold = self.config.context_warn_threshold;
+        let tokens = self.estimated_context_tokens();
+        (threshold > 0 && tokens >= threshold).then_some(tokens)
+    }
+
+    /// Pinned banner showing the session's system prompt, with edit/clear controls
+    fn render_system_prompt_banner(&self, prompt: String, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+        let edit_prompt = prompt.clone();
+
+        let banner_button = |id: &'static str, label: &'static str| {
+            div()
+                .id(id)
+                .px_2()
+                .py_px()
+                .rounded(px(4.))
+                .cursor_pointer()
+                .text_xs()
+                .text_color(theme.foreground_dim)
+                .hover(|s| s.bg(theme.background_highlight).text_color(theme.accent_cyan))
+                .child(label)
+        };
+
+        div()
+            .w_full()
+            .px_4()
+            .py_2()
+            .flex()
+            .items_center()
+            .gap_2()
+            .bg(theme.background_secondary)
+            .border_b_1()
+            .border_color(theme.border)
+            .child(
+                div()
+                    .text_xs()
+                    .font_weight(FontWeight::SEMIBOLD)
+                    .text_color(theme.accent_purple)
+                    .child("System prompt"),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .overflow_hidden()
+                    .text_ellipsis()
+                    .whitespace_nowrap()
+                    .text_xs()
+                    .text_color(theme.foreground_dim)
+                    .child(prompt),
+            )
+            .child(banner_button("system-prompt-edit", "edit").on_click(cx.listener(
+                move |this, _event, window, cx| {
+                    let value = format!("/system {}", edit_prompt);
+                    this.input_state.update(cx, |state, cx| {
+                        state.set_value(value, window, cx);
+                        state.focus(window, cx);
+                    });
+                },
+            )))
+            .child(banner_button("system-prompt-clear", "clear").on_click(cx.listener(
+                |this, _event, _window, cx| {
+                    this.system_prompt = None;
+                    cx.notify();
+                },
+            )))
+    }
+
+    /// Keep the list rows in step with `messages` (minus filtered ones).
+    ///
+    /// Rows are diffed by message id so only the changed tail is re-measured
+    /// and the scroll position survives appends.
+    pub fn sync_message_list(&mut self) {
+        let rows: Vec<String> = self
             .messages
             .iter()
-            .enumerate()
-            .map(|(msg_idx, msg)| {
-                let (bg_color, align_end, role_label, role_color) = match msg.role {
-                    MessageRole::User => (theme.user_message_bg, true, "You", theme.accent_green),
-                    MessageRole::Assistant => {
-                        (theme.assistant_message_bg, false, "Assistant", theme.accent_cyan)
-                    }
-                    MessageRole::System => {
-                        (theme.system_message_bg, false, "System", theme.accent_yellow)
-                    }
-                };
+            .filter(|msg| !(self.hide_system_messages && msg.role == MessageRole::System))
+            .map(|msg| msg.id.clone())
+            .collect();
+        if rows == self.message_rows {
+            return;
+        }
+
+        let common = rows
+            .iter()
+            .zip(&self.message_rows)
+            .take_while(|(new, old)| new == old)
+            .count();
+        self.message_list
+            .splice(common..self.message_rows.len(), rows.len() - common);
+        self.message_rows = rows;
+        self.selected_message = None;
+
+        if self.stick_to_bottom {
+            self.scroll_message_list_to_end();
+        }
+    }
+
+    /// First-run guidance shown below the list until the user sends something
+    fn render_empty_state(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
 
-                let msg_id = msg.id.clone();
-                let is_streaming = self.streaming_message_id.as_ref() == Some(&msg.id);
-                let use_segments = msg.role == MessageRole::Assistant && !msg.segments.is_empty();
+        let section_title = |title: &'static str| {
+            div()
+                .text_xs()
+                .font_weight(FontWeight::SEMIBOLD)
+                .text_color(theme.foreground_muted)
+                .child(title)
+        };
 
+        div()
+            .id("empty-state")
+            .flex_none()
+            .mx_4()
+            .mb_4()
+            .p_4()
+            .rounded_lg()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.background_secondary)
+            .flex()
+            .flex_col()
+            .gap_3()
+            .child(section_title("TRY ASKING"))
+            .child(
                 div()
-                    .w_full()
                     .flex()
-                    .flex_col()
-                    .mb_3()
+                    .flex_wrap()
+                    .gap_2()
+                    .children(SUGGESTED_PROMPTS.into_iter().enumerate().map(|(i, prompt)| {
+                        div()
+                            .id(SharedString::from(format!("suggested-prompt-{}", i)))
+                            .px_3()
+                            .py_1()
+                            .rounded_full()
+                            .border_1()
+                            .border_color(theme.border)
+                            .bg(theme.background_elevated)
+                            .cursor_pointer()
+                            .text_sm()
+                            .text_color(theme.foreground)
+                            .hover(|s| s.border_color(theme.accent_cyan))
+                            .on_click(cx.listener(move |this, _event, window, cx| {
+                                this.input_state.update(cx, |state, cx| {
+                                    state.set_value(prompt, window, cx);
+                                    state.focus(window, cx);
+                                });
+                            }))
+                            .child(prompt)
+                    })),
+            )
+            .child(section_title("HOW IT WORKS"))
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(theme.foreground_dim)
                     .child(
+                        "The Memory panel shows what the assistant recalled for your last \
+                        message, ranked by energy. The Knowledge panel lists the facts \
+                        (subject, predicate, object) it used, with their confidence. Both \
+                        fill in as you chat.",
+                    ),
+            )
+            .child(section_title("COMMANDS"))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_1()
+                    .children(EMPTY_STATE_COMMANDS.into_iter().map(|(command, description)| {
                         div()
-                            .w_full()
                             .flex()
-                            .when(align_end, |el| el.justify_end())
-                            .when(!align_end, |el| el.justify_start())
+                            .gap_2()
+                            .text_sm()
+                            .child(
+                                div()
+                                    .font_family(cx.theme().mono_font_family.clone())
+                                    .text_color(theme.accent_cyan)
+                                    .child(command),
+                            )
+                            .child(div().text_color(theme.foreground_dim).child(description))
+                    })),
+            )
+    }
+
+    /// Render one visible row of the message list
+    fn render_message_row(&self, row: usize, cx: &Context<Self>) -> AnyElement {
+        let Some(msg_idx) = self
+            .message_rows
+            .get(row)
+            .and_then(|id| self.messages.iter().position(|m| &m.id == id))
+        else {
+            return div().into_any_element();
+        };
+
+        let selected = self.selected_message == Some(row);
+
+        div()
+            .px_4()
+            .when(row == 0, |el| el.pt_4())
+            .when(row + 1 == self.message_rows.len(), |el| el.pb_1())
+            .child(
+                div()
+                    .rounded_lg()
+                    .border_1()
+                    .border_color(if selected {
+                        self.theme.accent_cyan.opacity(0.6)
+                    } else {
+                        gpui::transparent_black()
+                    })
+                    .child(self.render_message(msg_idx, &self.messages[msg_idx], cx)),
+            )
+            .into_any_element()
+    }
+
+    fn select_next_message(
+        &mut self,
+        _: &SelectNextMessage,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let last = self.message_rows.len().checked_sub(1);
+        let next = match (self.selected_message, last) {
+            (_, None) => None,
+            (Some(row), Some(last)) => Some((row + 1).min(last)),
+            (None, Some(last)) => Some(last),
+        };
+        self.select_message_row(next, cx);
+    }
+
+    fn select_previous_message(
+        &mut self,
+        _: &SelectPreviousMessage,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let last = self.message_rows.len().checked_sub(1);
+        let previous = match (self.selected_message, last) {
+            (_, None) => None,
+            (Some(row), Some(_)) => Some(row.saturating_sub(1)),
+            (None, Some(last)) => Some(last),
+        };
+        self.select_message_row(previous, cx);
+    }
+
+    pub fn resend_last_message(
+        &mut self,
+        _: &ResendLastMessage,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.resend_last_user_message(cx);
+    }
+
+    fn copy_selected_message(
+        &mut self,
+        _: &CopySelectedMessage,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(id) = self
+            .selected_message
+            .and_then(|row| self.message_rows.get(row))
+            .cloned()
+        {
+            self.copy_message(&id, cx);
+        }
+    }
+
+    fn clear_message_selection(
+        &mut self,
+        _: &ClearMessageSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select_message_row(None, cx);
+        self.input_state.update(cx, |state, cx| {
+            state.focus(window, cx);
+        });
+    }
+
+    /// Highlight a row and scroll it into view; moving the selection stops auto-follow
+    fn select_message_row(&mut self, row: Option<usize>, cx: &mut Context<Self>) {
+        self.selected_message = row;
+        if let Some(row) = row {
+            self.stick_to_bottom = false;
+            self.message_list.scroll_to_reveal_item(row);
+        }
+        cx.notify();
+    }
+
+    fn render_message(&self, msg_idx: usize, msg: &ChatMessage, cx: &Context<Self>) -> AnyElement {
+        let theme = &self.theme;
+        let (bg_color, align_end, role_label, role_color) = match msg.role {
+            MessageRole::User => (theme.user_message_bg, true, "You", theme.accent_green),
+            MessageRole::Assistant => {
+                (theme.assistant_message_bg, false, "Assistant", theme.accent_cyan)
+            }
+            MessageRole::System => {
+                (theme.system_message_bg, false, "System", theme.accent_yellow)
+            }
+        };
+
+        let msg_id = msg.id.clone();
+        let menu_msg_id = msg.id.clone();
+        let is_streaming = self.streaming_message_id.as_ref() == Some(&msg.id);
+        let show_raw = self.raw_view_messages.contains(&msg.id);
+        // A reply still streaming always shows its body
+        let collapsed = msg.collapsed && !is_streaming;
+        let collapse_msg_id = msg.id.clone();
+        let cleaned_content = Self::clean_response_content(&msg.content);
+        // Surrounding whitespace is always trimmed, so only flag real removals
+        let content_filtered = msg.role == MessageRole::Assistant
+            && !is_streaming
+            && cleaned_content != msg.content.trim();
+        let use_segments = !collapsed
+            && !show_raw
+            && msg.role == MessageRole::Assistant
+            && !msg.segments.is_empty();
+        let has_tools = !msg.tool_calls.is_empty()
+            || msg
+                .segments
+                .iter()
+                .any(|seg| matches!(seg, MessageSegment::ToolCall(_)));
+        let copy_tools_msg_id = msg.id.clone();
+        let retry_msg_id = msg.id.clone();
+        let compact = self.config.density == Density::Compact;
+        // Clamp in case the saved config was edited by hand
+        let max_width = self
+            .config
+            .max_message_width
+            .clamp(MIN_MESSAGE_WIDTH, MAX_MESSAGE_WIDTH)
+            * if compact { 1.5 } else { 1. };
+
+        div()
+            .w_full()
+            .flex()
+            .flex_col()
+            .when(compact, |el| el.mb_1())
+            .when(!compact, |el| el.mb_3())
+            .child(
+                div()
+                    .w_full()
+                    .flex()
+                    .when(align_end, |el| el.justify_end())
+                    .when(!align_end, |el| el.justify_start())
+                    .child(
+                        div()
+                            .max_w(px(max_width))
+                            .when(compact, |el| el.px_2().py_1())
+                            .when(!compact, |el| el.p_3())
+                            .rounded_lg()
+                            .bg(bg_color)
+                            .on_mouse_down(
+                                MouseButton::Right,
+                                cx.listener(move |this, event: &MouseDownEvent, _window, cx| {
+                                    this.message_menu = Some((menu_msg_id.clone(), event.position));
+                                    cx.notify();
+                                }),
+                            )
                             .child(
                                 div()
-                                    .max_w(px(600.))
-                                    .p_3()
-                                    .rounded_lg()
-                                    .bg(bg_color)
+                                    .flex()
+                                    .justify_between()
+                                    .gap_2()
+                                    .when(!collapsed, |el| el.mb_1())
                                     .child(
                                         div()
+                                            .id(SharedString::from(format!("collapse-message-{}", msg.id)))
                                             .flex()
-                                            .justify_between()
-                                            .mb_1()
+                                            .items_center()
+                                            .gap_1()
+                                            .min_w_0()
+                                            .cursor_pointer()
+                                            .on_click(cx.listener(move |this, _event, _window, cx| {
+                                                this.toggle_message_collapsed(&collapse_msg_id, cx);
+                                            }))
+                                            .child(
+                                                div()
+                                                    .text_xs()
+                                                    .text_color(theme.foreground_muted)
+                                                    .child(if collapsed { "▶" } else { "▼" }),
+                                            )
                                             .child(
                                                 div()
+                                                    .flex_none()
                                                     .text_sm()
                                                     .font_weight(FontWeight::SEMIBOLD)
                                                     .text_color(role_color)
-                                                    .child(role_label),
+                                                    .child(match &msg.answered_as {
+                                                        Some(user) => format!("{} (as {})", role_label, user),
+                                                        None => role_label.to_string(),
+                                                    }),
                                             )
-                                            .child(
-                                                div()
-                                                    .text_xs()
-                                                    .text_color(theme.foreground_muted)
-                                                    .child(msg.timestamp.clone()),
-                                            ),
+                                            .when(collapsed, |el| {
+                                                el.child(
+                                                    div()
+                                                        .truncate()
+                                                        .text_sm()
+                                                        .text_color(theme.foreground_dim)
+                                                        .child(one_line_preview(&cleaned_content, COLLAPSED_PREVIEW_CHARS)),
+                                                )
+                                            }),
                                     )
-                                    .when(use_segments, |el| {
-                                        // Render segments inline (text + tool calls)
-                                        el.children(msg.segments.iter().enumerate().map(|(seg_idx, seg)| {
-                                            match seg {
-                                                MessageSegment::Text(text) => {
-                                                    let cleaned = Self::clean_response_content(text);
-                                                    if cleaned.is_empty() {
-                                                        div().into_any_element()
-                                                    } else {
-                                                        let content_view = TextView::markdown(
-                                                            SharedString::from(format!("msg-{}-seg-{}", msg_idx, seg_idx)),
-                                                            cleaned,
-                                                        );
-                                                        div().text_sm().child(content_view).into_any_element()
-                                                    }
-                                                }
-                                                MessageSegment::ToolCall(tc) => {
-                                                    let tool_id = tc.id.clone();
-                                                    let msg_id_clone = msg_id.clone();
-                                                    let status_icon = match tc.status {
-                                                        ToolStatus::Running => "⏳",
-                                                        ToolStatus::Completed => "✅",
-                                                        ToolStatus::Failed => "❌",
-                                                    };
-                                                    let status_color = match tc.status {
-                                                        ToolStatus::Running => theme.accent_yellow,
-                                                        ToolStatus::Completed => theme.accent_green,
-                                                        ToolStatus::Failed => theme.accent_red,
-                                                    };
+                                    .child(self.render_message_meta(msg, content_filtered, cx)),
+                            )
+                            .when(!collapsed && !msg.reasoning.is_empty(), |el| {
+                                el.child(self.render_reasoning_block(msg, cx))
+                            })
+                            .when(!collapsed && show_raw, |el| {
+                                // Verbatim backend text, bypassing markdown and the cleaner
+                                el.child(
+                                    div()
+                                        .p_2()
+                                        .rounded_md()
+                                        .bg(theme.background_secondary)
+                                        .font_family(cx.theme().mono_font_family.clone())
+                                        .text_xs()
+                                        .text_color(theme.foreground_dim)
+                                        .child(msg.content.clone()),
+                                )
+                            })
+                            .when(use_segments, |el| {
+                                // Render segments inline (text + tool calls)
+                                let live_seg_idx = msg.segments.len() - 1;
+                                el.children(msg.segments.iter().enumerate().map(|(seg_idx, seg)| {
+                                    match seg {
+                                        MessageSegment::Text(text) => {
+                                            let cleaned = Self::clean_response_content(text);
+                                            if cleaned.is_empty() {
+                                                div().into_any_element()
+                                            } else if is_streaming && seg_idx == live_seg_idx {
+                                                // The growing segment stays plain text until Done: re-parsing
+                                                // markdown on every chunk is quadratic in the reply length
+                                                div()
+                                                    .w_full()
+                                                    .text_sm()
+                                                    .whitespace_normal()
+                                                    .child(cleaned)
+                                                    .into_any_element()
+                                            } else {
+                                                let content_view = self.render_markdown_content(
+                                                    format!("msg-{}-seg-{}", msg_idx, seg_idx),
+                                                    cleaned,
+                                                    cx,
+                                                );
+                                                div().text_sm().child(content_view).into_any_element()
+                                            }
+                                        }
+                                        MessageSegment::ToolCall(tc) => {
+                                            let tool_id = tc.id.clone();
+                                            let msg_id_clone = msg_id.clone();
+                                            let suspect = self.tool_result_suspect(&tc.status, tc.result.as_deref());
+                                            let status_icon = if suspect {
+                                                self.icons().warning
+                                            } else {
+                                                self.icons().tool_status(&tc.status)
+                                            };
+                                            let status_color = match tc.status {
+                                                _ if suspect => theme.accent_orange,
+                                                ToolStatus::Running => theme.accent_yellow,
+                                                ToolStatus::Completed => theme.accent_green,
+                                                ToolStatus::Failed => theme.accent_red,
+                                            };
 
+                                            div()
+                                                .my_2()
+                                                .rounded_md()
+                                                .border_1()
+                                                .border_color(theme.border)
+                                                .bg(theme.background_secondary)
+                                                .overflow_hidden()
+                                                .child(
                                                     div()
-                                                        .my_2()
-                                                        .rounded_md()
-                                                        .border_1()
-                                                        .border_color(theme.border)
-                                                        .bg(theme.background_secondary)
-                                                        .overflow_hidden()
+                                                        .id(SharedString::from(format!("tool-header-{}", tc.id)))
+                                                        .flex()
+                                                        .items_center()
+                                                        .gap_2()
+                                                        .px_3()
+                                                        .py_2()
+                                                        .cursor_pointer()
+                                                        .hover(|s| s.bg(theme.background_highlight))
+                                                        .on_click(cx.listener(move |this, _event, _window, cx| {
+                                                            this.toggle_inline_tool(&msg_id_clone, &tool_id, cx);
+                                                        }))
                                                         .child(
                                                             div()
-                                                                .id(SharedString::from(format!("tool-header-{}", tc.id)))
-                                                                .flex()
-                                                                .items_center()
-                                                                .gap_2()
-                                                                .px_3()
-                                                                .py_2()
-                                                                .cursor_pointer()
-                                                                .hover(|s| s.bg(theme.background_highlight))
-                                                                .on_click(cx.listener(move |this, _event, _window, cx| {
-                                                                    this.toggle_inline_tool(&msg_id_clone, &tool_id, cx);
-                                                                }))
-                                                                .child(
-                                                                    div()
-                                                                        .text_xs()
-                                                                        .text_color(theme.foreground_muted)
-                                                                        .child(if tc.expanded { "▼" } else { "▶" }),
-                                                                )
-                                                                .child(div().text_sm().child(status_icon))
-                                                                .child(
-                                                                    div()
-                                                                        .text_sm()
-                                                                        .font_weight(FontWeight::MEDIUM)
-                                                                        .text_color(status_color)
-                                                                        .child(format!("🔧 {}", tc.name)),
-                                                                )
-                                                                .when(tc.status == ToolStatus::Running, |el| {
-                                                                    el.child(
-                                                                        div()
-                                                                            .text_xs()
-                                                                            .text_color(theme.foreground_muted)
-                                                                            .child("running...")
-                                                                    )
-                                                                }),
+                                                                .text_xs()
+                                                                .text_color(theme.foreground_muted)
+                                                                .child(if tc.expanded { "▼" } else { "▶" }),
                                                         )
-                                                        .when(tc.expanded, |el| {
-                                                            let result_text = tc.result.clone().unwrap_or_else(|| {
-                                                                if tc.status == ToolStatus::Running {
-                                                                    "Executing...".to_string()
-                                                                } else {
-                                                                    "No result".to_string()
-                                                                }
-                                                            });
-                                                            let truncated = if result_text.len() > 500 {
-                                                                format!("{}...", &result_text[..500])
-                                                            } else {
-                                                                result_text
-                                                            };
-                                                            let result_view = TextView::markdown(
-                                                                SharedString::from(format!("tool-result-{}", tc.id)),
-                                                                truncated,
-                                                            );
-
+                                                        .child(self.render_tool_status_icon(&tc.id, status_icon, tc.status == ToolStatus::Running))
+                                                        .child(
+                                                            div()
+                                                                .text_sm()
+                                                                .font_weight(FontWeight::MEDIUM)
+                                                                .text_color(status_color)
+                                                                .child(format!("{} {}", self.icons().tool, tc.name)),
+                                                        )
+                                                        .when_some(tc.duration_ms, |el, ms| {
                                                             el.child(
                                                                 div()
-                                                                    .id(SharedString::from(format!("tool-content-{}", tc.id)))
-                                                                    .px_3()
-                                                                    .py_2()
-                                                                    .border_t_1()
-                                                                    .border_color(theme.border)
-                                                                    .bg(theme.background)
-                                                                    .max_h(px(200.))
-                                                                    .overflow_y_scroll()
-                                                                    .child(
-                                                                        div()
-                                                                            .text_xs()
-                                                                            .text_color(theme.foreground_dim)
-                                                                            .child(result_view)
-                                                                    )
+                                                                    .text_xs()
+                                                                    .text_color(theme.foreground_muted)
+                                                                    .child(format!("({})", format_duration(ms)))
                                                             )
                                                         })
-                                                        .into_any_element()
-                                                }
-                                            }
-                                        }))
-                                    })
-                                    .when(!use_segments, |el| {
-                                        // Fallback: render plain content
-                                        let cleaned_content = Self::clean_response_content(&msg.content);
-                                        let content_view = TextView::markdown(
-                                            SharedString::from(format!("msg-{}", msg_idx)),
-                                            cleaned_content,
-                                        );
-                                        el.child(div().text_sm().child(content_view))
-                                    })
-                                    .when(is_streaming && msg.segments.is_empty() && msg.content.is_empty(), |el| {
-                                        el.child(
+                                                        .when(tc.status == ToolStatus::Running, |el| {
+                                                            el.child(
+                                                                div()
+                                                                    .text_xs()
+                                                                    .text_color(theme.foreground_muted)
+                                                                    .child("running...")
+                                                            )
+                                                        })
+                                                        .when(tc.status == ToolStatus::Failed, |el| {
+                                                            el.child(self.render_tool_retry_button("inline", &tc.id, cx))
+                                                        })
+                                                        .when(tc.expanded && tc.result.is_some(), |el| {
+                                                            el.child(self.render_tool_save_button("inline", &tc.id, cx))
+                                                        }),
+                                                )
+                                                .when(tc.expanded, |el| {
+                                                    let mode = self.config.tool_display;
+                                                    let el = el.when(mode.shows_args(), |el| {
+                                                        el.child(self.expand_transition(
+                                                            SharedString::from(format!("tool-args-expand-{}", tc.id)),
+                                                            self.render_tool_arguments("inline", &tc.id, &tc.arguments, cx),
+                                                            None,
+                                                        ))
+                                                    });
+                                                    if !mode.shows_result() {
+                                                        return el;
+                                                    }
+                                                    // Image results render as the image itself
+                                                    if let Some(ToolImage::Ready { image, reference }) = self.tool_images.get(&tc.id) {
+                                                        return el.child(self.expand_transition(
+                                                            SharedString::from(format!("tool-image-expand-{}", tc.id)),
+                                                            self.render_tool_image(&tc.id, image.clone(), reference.clone(), cx),
+                                                            None,
+                                                        ));
+                                                    }
+                                                    let result_text = tc.result.clone().unwrap_or_else(|| {
+                                                        if tc.status == ToolStatus::Running {
+                                                            "Executing...".to_string()
+                                                        } else {
+                                                            "No result".to_string()
+                                                        }
+                                                    });
+                                                    let truncated = truncate_chars(&result_text, 500);
+                                                    let result_view = TextView::markdown(
+                                                        SharedString::from(format!("tool-result-{}", tc.id)),
+                                                        truncated,
+                                                    )
+                                                    .selectable(true);
+
+                                                    let result_block = div()
+                                                        .id(SharedString::from(format!("tool-content-{}", tc.id)))
+                                                        .px_3()
+                                                        .py_2()
+                                                        .border_t_1()
+                                                        .border_color(if suspect { theme.accent_red } else { theme.border })
+                                                        .bg(if suspect { theme.accent_red.opacity(0.08) } else { theme.background })
+                                                        .max_h(px(200.))
+                                                        // Scroll both ways so unbroken lines (e.g. JSON) aren't clipped
+                                                        .overflow_scroll()
+                                                        .child(
+                                                            div()
+                                                                .w_full()
+                                                                .whitespace_normal()
+                                                                .text_xs()
+                                                                .text_color(if suspect { theme.accent_red } else { theme.foreground_dim })
+                                                                .child(result_view)
+                                                        );
+                                                    el.child(self.expand_transition(
+                                                        SharedString::from(format!("tool-result-expand-{}", tc.id)),
+                                                        result_block,
+                                                        Some(200.),
+                                                    ))
+                                                })
+                                                .into_any_element()
+                                        }
+                                    }
+                                }))
+                            })
+                            .when(!collapsed && !use_segments && !show_raw, |el| {
+                                // Fallback: render plain content
+                                let content_view = self.render_markdown_content(
+                                    format!("msg-{}", msg_idx),
+                                    cleaned_content,
+                                    cx,
+                                );
+                                el.child(div().text_sm().child(content_view))
+                            })
+                            .when(!collapsed && !msg.citations.is_empty(), |el| {
+                                el.child(self.render_citations(msg, cx))
+                            })
+                            .when_some(msg.stream_error.clone().filter(|_| !collapsed), |el, error| {
+                                // Recoverable error: the reply above is partial
+                                el.child(
+                                    div()
+                                        .mt_2()
+                                        .px_2()
+                                        .py_1()
+                                        .rounded_md()
+                                        .bg(theme.accent_orange.opacity(0.15))
+                                        .text_xs()
+                                        .text_color(theme.accent_orange)
+                                        .child(format!("⚠️ Response interrupted: {}", error)),
+                                )
+                            })
+                            .when(!collapsed && msg.failed && !self.is_loading, |el| {
+                                el.child(
+                                    div()
+                                        .mt_2()
+                                        .flex()
+                                        .child(
+                                            div()
+                                                .id(SharedString::from(format!("retry-message-{}", msg.id)))
+                                                .px_2()
+                                                .py_px()
+                                                .rounded(px(4.))
+                                                .border_1()
+                                                .border_color(theme.accent_red)
+                                                .cursor_pointer()
+                                                .text_xs()
+                                                .text_color(theme.accent_red)
+                                                .hover(|s| s.bg(theme.accent_red.opacity(0.15)))
+                                                .on_click(cx.listener(move |this, _event, _window, cx| {
+                                                    this.retry_failed_message(&retry_msg_id, cx);
+                                                }))
+                                                .child(if msg.content == EMPTY_RESPONSE_NOTE {
+                                                    "↻ Regenerate"
+                                                } else {
+                                                    "↻ Retry"
+                                                }),
+                                        ),
+                                )
+                            })
+                            .when(is_streaming && msg.segments.is_empty() && msg.content.is_empty() && msg.reasoning.is_empty(), |el| {
+                                el.child(
+                                    div()
+                                        .text_sm()
+                                        .text_color(theme.foreground_muted)
+                                        .child("Thinking...")
+                                )
+                            })
+                            .when_some(is_streaming.then(|| self.stream_rate()).flatten(), |el, (words, chars_per_sec)| {
+                                // Live throughput; tokens use the same chars / 4 estimate as the context warning
+                                el.child(
+                                    div()
+                                        .mt_1()
+                                        .text_xs()
+                                        .text_color(theme.foreground_muted)
+                                        .child(format!(
+                                            "{} words • {:.0} chars/s (~{:.0} tok/s)",
+                                            words,
+                                            chars_per_sec,
+                                            chars_per_sec / 4.
+                                        )),
+                                )
+                            })
+                            .when(is_streaming && self.stream_is_idle(), |el| {
+                                el.child(
+                                    div()
+                                        .mt_1()
+                                        .text_sm()
+                                        .text_color(theme.foreground_muted)
+                                        .child("● ● ●")
+                                        .with_animation(
+                                            "typing-indicator",
+                                            pulse_animation(),
+                                            |el, delta| el.opacity(delta),
+                                        ),
+                                )
+                            })
+                            .when(!collapsed && has_tools && !is_streaming, |el| {
+                                el.child(
+                                    div()
+                                        .mt_2()
+                                        .flex()
+                                        .justify_end()
+                                        .child(
                                             div()
-                                                .text_sm()
+                                                .id(SharedString::from(format!("copy-tools-{}", msg.id)))
+                                                .px_2()
+                                                .py_px()
+                                                .rounded(px(4.))
+                                                .cursor_pointer()
+                                                .text_xs()
                                                 .text_color(theme.foreground_muted)
-                                                .child("Thinking...")
-                                        )
-                                    }),
-                            ),
+                                                .hover(|s| s.bg(theme.background_highlight).text_color(theme.accent_cyan))
+                                                .on_click(cx.listener(move |this, _event, _window, cx| {
+                                                    this.copy_tool_results(&copy_tools_msg_id, cx);
+                                                }))
+                                                .child("Copy tool results"),
+                                        ),
+                                )
+                            }),
+                    ),
+            )
+            .into_any_element()
+    }
+
+    /// Right-click menu for a message (Copy, Quote, Branch, Delete)
+    pub fn render_message_menu(
+        &self,
+        msg_id: String,
+        position: Point<Pixels>,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = &self.theme;
+        let is_streaming = self.streaming_message_id.as_ref() == Some(&msg_id);
+
+        let menu_item = |id: &'static str, label: &'static str, enabled: bool| {
+            div()
+                .id(id)
+                .w_full()
+                .px_3()
+                .py_1()
+                .text_sm()
+                .text_color(if enabled {
+                    theme.foreground
+                } else {
+                    theme.foreground_muted
+                })
+                .when(enabled, |el| {
+                    el.cursor_pointer()
+                        .hover(|s| s.bg(theme.background_highlight))
+                })
+                .child(label)
+        };
+
+        let copy_id = msg_id.clone();
+        let quote_id = msg_id.clone();
+        // "Ask as..." resends a user message under each other known user
+        let ask_as_users: Vec<String> = if self.is_loading
+            || !self
+                .messages
+                .iter()
+                .any(|m| m.id == msg_id && m.role == MessageRole::User)
+        {
+            vec![]
+        } else {
+            self.available_users
+                .iter()
+                .filter(|user| **user != self.current_user_id)
+                .cloned()
+                .collect()
+        };
+        let ask_as_id = msg_id.clone();
+        let branch_id = msg_id.clone();
+        let delete_id = msg_id;
+
+        deferred(
+            anchored().position(position).snap_to_window().child(
+                div()
+                    .id("message-menu")
+                    .occlude()
+                    .w(px(160.))
+                    .py_1()
+                    .bg(theme.background_elevated)
+                    .border_1()
+                    .border_color(theme.border)
+                    .rounded(px(6.))
+                    .shadow_lg()
+                    .flex()
+                    .flex_col()
+                    .child(menu_item("message-menu-copy", "Copy", true).on_click(cx.listener(
+                        move |this, _event, _window, cx| {
+                            this.copy_message(&copy_id, cx);
+                            this.message_menu = None;
+                            cx.notify();
+                        },
+                    )))
+                    .child(menu_item("message-menu-quote", "Quote", true).on_click(cx.listener(
+                        move |this, _event, window, cx| {
+                            this.quote_message(&quote_id, window, cx);
+                            this.message_menu = None;
+                            cx.notify();
+                        },
+                    )))
+                    .child(
+                        menu_item("message-menu-branch", "Branch from here", !self.is_loading)
+                            .on_click(cx.listener(move |this, _event, _window, cx| {
+                                this.branch_from(&branch_id, cx);
+                            })),
                     )
+                    .child(
+                        menu_item("message-menu-delete", "Delete", !is_streaming).on_click(
+                            cx.listener(move |this, _event, _window, cx| {
+                                this.delete_message(&delete_id, cx);
+                            }),
+                        ),
+                    )
+                    .when(!ask_as_users.is_empty(), |el| {
+                        el.child(div().my_1().h(px(1.)).w_full().bg(theme.border))
+                            .child(
+                                div()
+                                    .px_3()
+                                    .py_px()
+                                    .text_xs()
+                                    .text_color(theme.foreground_muted)
+                                    .child("Ask as..."),
+                            )
+                    })
+                    .children(ask_as_users.into_iter().map(|user| {
+                        let msg_id = ask_as_id.clone();
+                        div()
+                            .id(SharedString::from(format!("message-menu-ask-as-{}", user)))
+                            .w_full()
+                            .pl_5()
+                            .pr_3()
+                            .py_1()
+                            .text_sm()
+                            .text_color(theme.foreground)
+                            .cursor_pointer()
+                            .hover(|s| s.bg(theme.background_highlight))
+                            .overflow_hidden()
+                            .child(user.clone())
+                            .on_click(cx.listener(move |this, _event, _window, cx| {
+                                this.message_menu = None;
+                                this.ask_as(&msg_id, user.clone(), cx);
+                            }))
+                    })),
+            ),
+        )
+    }
+
+    /// Copy a message's content to the clipboard
+    pub fn copy_message(&mut self, msg_id: &str, cx: &mut Context<Self>) {
+        if let Some(msg) = self.messages.iter().find(|m| m.id == msg_id) {
+            let text = if msg.role == MessageRole::Assistant {
+                Self::clean_response_content(&msg.content)
+            } else {
+                msg.content.clone()
+            };
+            self.copy_to_clipboard(text, cx);
+        }
+    }
+
+    /// Copy one tool call's arguments as pretty JSON, from the live panel or a message
+    pub fn copy_tool_args(&mut self, tool_id: &str, cx: &mut Context<Self>) {
+        let arguments = self
+            .live_tool_calls
+            .iter()
+            .find(|tc| tc.id == tool_id)
+            .map(|tc| &tc.arguments)
+            .or_else(|| {
+                self.messages
+                    .iter()
+                    .flat_map(|msg| &msg.segments)
+                    .find_map(|seg| match seg {
+                        MessageSegment::ToolCall(tc) if tc.id == tool_id => Some(&tc.arguments),
+                        _ => None,
+                    })
+            });
+        let Some(arguments) = arguments else {
+            return;
+        };
+        let text =
+            serde_json::to_string_pretty(arguments).unwrap_or_else(|_| arguments.to_string());
+        self.copy_to_clipboard(text, cx);
+    }
+
+    /// Copy every tool call of a message (name, arguments, result) to the clipboard
+    pub fn copy_tool_results(&mut self, msg_id: &str, cx: &mut Context<Self>) {
+        let Some(msg) = self.messages.iter().find(|m| m.id == msg_id) else {
+            return;
+        };
+
+        // Inline segments carry results; the summary list is the fallback
+        let mut tools: Vec<(String, serde_json::Value, Option<String>)> = msg
+            .segments
+            .iter()
+            .filter_map(|seg| match seg {
+                MessageSegment::ToolCall(tc) => {
+                    Some((tc.name.clone(), tc.arguments.clone(), tc.result.clone()))
+                }
+                MessageSegment::Text(_) => None,
+            })
+            .collect();
+        if tools.is_empty() {
+            tools = msg
+                .tool_calls
+                .iter()
+                .map(|tc| (tc.name.clone(), tc.arguments.clone(), tc.result.clone()))
+                .collect();
+        }
+        if tools.is_empty() {
+            return;
+        }
+
+        let text = tools
+            .into_iter()
+            .map(|(name, arguments, result)| {
+                let args = serde_json::to_string_pretty(&arguments)
+                    .unwrap_or_else(|_| arguments.to_string());
+                format!(
+                    "## {}\n{}\n{}",
+                    name,
+                    args,
+                    result.unwrap_or_else(|| "(no result)".to_string())
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        self.copy_to_clipboard(text, cx);
+    }
+
+    /// Resend a user message answered with another user's memory, without switching
+    pub fn ask_as(&mut self, msg_id: &str, user_id: String, cx: &mut Context<Self>) {
+        let Some(text) = self
+            .messages
+            .iter()
+            .find(|m| m.id == msg_id && m.role == MessageRole::User)
+            .map(|m| m.content.clone())
+        else {
+            return;
+        };
+        self.send_message_as(text, Some(user_id), cx);
+    }
+
+    /// Fork the conversation up to and including a message into a new session.
+    /// The original stays saved; the backend context is reseeded with the kept turns.
+    pub fn branch_from(&mut self, msg_id: &str, cx: &mut Context<Self>) {
+        if self.is_loading {
+            return;
+        }
+        let Some(index) = self.messages.iter().position(|m| m.id == msg_id) else {
+            return;
+        };
+        // Above the divider is another user's conversation; don't carry it over
+        let start = self.session_start();
+        if index < start {
+            self.show_transient_message(
+                "Messages from before the user switch can't be branched from.".to_string(),
+                cx,
+            );
+            return;
+        }
+
+        // Save the original before it's truncated
+        self.persist_session(cx);
+        let parent = std::mem::replace(
+            &mut self.session_id,
+            uuid::Uuid::new_v4().to_string()[..8].to_string(),
+        );
+        // The branch keeps the original's title; lineage tells them apart
+        self.session_meta.branched_from = Some(parent.clone());
+        self.messages.truncate(index + 1);
+        self.live_tool_calls.clear();
+        self.expanded_tool_groups.clear();
+        self.current_tool_calls.clear();
+        self.selected_message = None;
+        self.message_menu = None;
+
+        let seed: Vec<SeedMessage> = self.messages[start..]
+            .iter()
+            .filter(|m| m.role != MessageRole::System && !m.failed)
+            .map(|m| SeedMessage {
+                role: m.role.as_str().to_string(),
+                content: if m.role == MessageRole::Assistant {
+                    Self::clean_response_content(&m.content)
+                } else {
+                    m.content.clone()
+                },
             })
             .collect();
+        self.messages.push(ChatMessage::system(format!(
+            "Branched from session {}. Use /load {} to go back.",
+            parent, parent
+        )));
+        self.persist_session(cx);
+
+        let api_client = self.api_client.clone();
+        let session_id = self.session_id.clone();
+        cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_spawn(async move { api_client.seed_session(&session_id, seed) })
+                .await;
+            if let Err(e) = result {
+                let _ = this.update(cx, |app, cx| {
+                    app.report_error(format!(
+                        "⚠️ Branch created, but the backend could not load its history: {}",
+                        e
+                    ));
+                    cx.notify();
+                });
+            }
+        })
+        .detach();
+        cx.notify();
+    }
+
+    /// Prefix the input with the message quoted as markdown
+    pub fn quote_message(&mut self, msg_id: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(msg) = self.messages.iter().find(|m| m.id == msg_id) else {
+            return;
+        };
+        let text = if msg.role == MessageRole::Assistant {
+            Self::clean_response_content(&msg.content)
+        } else {
+            msg.content.clone()
+        };
+        let quoted: String = text.lines().map(|line| format!("> {}\n", line)).collect();
+        let current = self.input_state.read(cx).value().to_string();
+        let value = format!("{}\n{}", quoted, current);
+        self.input_state.update(cx, |state, cx| {
+            state.set_value(value, window, cx);
+            state.focus(window, cx);
+        });
+    }
+
+    /// Remove a single message (never the one currently streaming)
+    pub fn delete_message(&mut self, msg_id: &str, cx: &mut Context<Self>) {
+        if self.streaming_message_id.as_deref() == Some(msg_id) {
+            return;
+        }
+        self.messages.retain(|m| m.id != msg_id);
+        self.message_menu = None;
+        cx.notify();
+    }
+
+    /// Stop following new content when the user scrolls up; resume at the bottom
+    fn handle_messages_scroll(
+        &mut self,
+        event: &ScrollWheelEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let scrolling_up = event.delta.pixel_delta(px(16.)).y > px(0.);
+        let stick = if scrolling_up {
+            false
+        } else {
+            let offset = self.message_list.scroll_px_offset_for_scrollbar();
+            let max_offset = self.message_list.max_offset_for_scrollbar();
+            -offset.y >= max_offset.height - px(8.)
+        };
+        if stick != self.stick_to_bottom {
+            self.stick_to_bottom = stick;
+            if stick {
+                self.unseen_stream_content = false;
+            }
+            cx.notify();
+        }
+    }
+
+    /// Jump to the newest message and resume auto-scrolling
+    pub fn scroll_messages_to_bottom(&mut self, cx: &mut Context<Self>) {
+        self.stick_to_bottom = true;
+        self.unseen_stream_content = false;
+        self.scroll_message_list_to_end();
+        cx.notify();
+    }
+
+    /// Keep streamed content in view, or flag it as unseen when scrolled up
+    pub fn follow_stream_content(&mut self) {
+        if self.stick_to_bottom {
+            self.scroll_message_list_to_end();
+        } else {
+            self.unseen_stream_content = true;
+        }
+    }
+
+    /// Scroll past the last row so the list keeps following new content
+    pub fn scroll_message_list_to_end(&mut self) {
+        self.message_list.scroll_to(ListOffset {
+            item_ix: self.message_list.item_count(),
+            offset_in_item: px(0.),
+        });
+    }
+
+    /// Right side of a message header: per-message actions and the timestamp.
+    /// `content_filtered` marks replies the cleaner removed text from.
+    fn render_message_meta(
+        &self,
+        msg: &ChatMessage,
+        content_filtered: bool,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = &self.theme;
+        let show_raw = self.raw_view_messages.contains(&msg.id);
+        let msg_id = msg.id.clone();
+        let edited_msg_id = msg.id.clone();
 
         div()
-            .id("messages-container")
-            .flex_1()
-            .overflow_y_scroll()
+            .flex()
+            .items_center()
+            .gap_2()
+            .when(content_filtered, |el| {
+                el.child(
+                    div()
+                        .id(SharedString::from(format!("edited-dot-{}", msg.id)))
+                        .size(px(6.))
+                        .rounded_full()
+                        .cursor_pointer()
+                        .bg(theme.foreground_muted)
+                        .tooltip(|window, cx| {
+                            Tooltip::new("Edited: tool output was hidden. Click for the raw reply")
+                                .build(window, cx)
+                        })
+                        .on_click(cx.listener(move |this, _event, _window, cx| {
+                            this.toggle_raw_view(&edited_msg_id, cx);
+                        })),
+                )
+            })
+            .when(msg.role == MessageRole::Assistant, |el| {
+                el.child(
+                    div()
+                        .id(SharedString::from(format!("raw-toggle-{}", msg.id)))
+                        .px_1()
+                        .rounded(px(4.))
+                        .cursor_pointer()
+                        .text_xs()
+                        .text_color(if show_raw {
+                            theme.accent_cyan
+                        } else {
+                            theme.foreground_muted
+                        })
+                        .hover(|s| s.bg(theme.background_highlight))
+                        .tooltip(|window, cx| Tooltip::new("Toggle raw view").build(window, cx))
+                        .on_click(cx.listener(move |this, _event, _window, cx| {
+                            this.toggle_raw_view(&msg_id, cx);
+                        }))
+                        .child("{}"),
+                )
+            })
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(theme.foreground_muted)
+                    .child(format_timestamp(
+                        &msg.timestamp,
+                        self.config.timestamp_format,
+                        self.config.clock_12h,
+                    )),
+            )
+    }
+
+    /// Fold a message down to its header and a one-line summary, or unfold it
+    pub fn toggle_message_collapsed(&mut self, msg_id: &str, cx: &mut Context<Self>) {
+        if let Some(msg) = self.messages.iter_mut().find(|m| m.id == msg_id) {
+            msg.collapsed = !msg.collapsed;
+            cx.notify();
+        }
+    }
+
+    /// Toggle showing a message's unprocessed backend text
+    pub fn toggle_raw_view(&mut self, msg_id: &str, cx: &mut Context<Self>) {
+        if !self.raw_view_messages.remove(msg_id) {
+            self.raw_view_messages.insert(msg_id.to_string());
+        }
+        cx.notify();
+    }
+
+    /// Render markdown text, giving each fenced code block its own copy button
+    fn render_markdown_content(&self, id: String, text: String, cx: &Context<Self>) -> AnyElement {
+        let theme = &self.theme;
+
+        // Escape hatch for content the markdown parser mangles (tables, LaTeX)
+        if !self.config.render_markdown {
+            return div()
+                .id(SharedString::from(id))
+                .w_full()
+                .whitespace_normal()
+                .font_family(cx.theme().mono_font_family.clone())
+                .text_color(theme.foreground)
+                .child(text)
+                .into_any_element();
+        }
+
+        let blocks = split_code_blocks(&text);
+
+        if !blocks.iter().any(|b| matches!(b, ContentBlock::Code { .. })) {
+            return TextView::markdown(SharedString::from(id), text)
+                .selectable(true)
+                .into_any_element();
+        }
+
+        div()
+            .flex()
+            .flex_col()
+            .children(blocks.into_iter().enumerate().map(|(block_idx, block)| {
+                let block_id = format!("{}-block-{}", id, block_idx);
+                match block {
+                    ContentBlock::Markdown(markdown) => {
+                        TextView::markdown(SharedString::from(block_id), markdown)
+                            .selectable(true)
+                            .into_any_element()
+                    }
+                    ContentBlock::Code { language, code } => {
+                        // Re-wrap in a fence so the markdown view keeps highlighting
+                        let fenced = format!("```{}\n{}\n```", language, code);
+                        div()
+                            .relative()
+                            .my_1()
+                            .child(
+                                TextView::markdown(SharedString::from(block_id.clone()), fenced)
+                                    .selectable(true),
+                            )
+                            .child(
+                                div()
+                                    .absolute()
+                                    .top_1()
+                                    .right_1()
+                                    .flex()
+                                    .items_center()
+                                    .gap_2()
+                                    .when(!language.is_empty(), |el| {
+                                        el.child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(theme.foreground_muted)
+                                                .child(language.clone()),
+                                        )
+                                    })
+                                    .child(
+                                        div()
+                                            .id(SharedString::from(format!("{}-copy", block_id)))
+                                            .px_2()
+                                            .py_px()
+                                            .rounded(px(4.))
+                                            .cursor_pointer()
+                                            .bg(theme.background_elevated)
+                                            .text_xs()
+                                            .text_color(theme.foreground_dim)
+                                            .hover(|s| s.bg(theme.background_highlight).text_color(theme.accent_cyan))
+                                            .on_click(cx.listener(move |this, _event, _window, cx| {
+                                                this.copy_to_clipboard(code.clone(), cx);
+                                            }))
+                                            .child("Copy"),
+                                    ),
+                            )
+                            .into_any_element()
+                    }
+                }
+            }))
+            .into_any_element()
+    }
+
+    /// Tool status icon, pulsing while the tool is running
+    pub fn render_tool_status_icon(
+        &self,
+        tool_id: &str,
+        icon: &'static str,
+        running: bool,
+    ) -> AnyElement {
+        let icon_el = div().text_sm().child(icon);
+        if running {
+            icon_el
+                .with_animation(
+                    SharedString::from(format!("tool-pulse-{}", tool_id)),
+                    pulse_animation(),
+                    |el, delta| el.opacity(delta),
+                )
+                .into_any_element()
+        } else {
+            icon_el.into_any_element()
+        }
+    }
+
+    /// Retry button for a failed tool call (disabled if the backend refused a retry)
+    pub fn render_tool_retry_button(
+        &self,
+        id_prefix: &str,
+        tool_id: &str,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        let theme = &self.theme;
+        let button_id = SharedString::from(format!("{}-retry-{}", id_prefix, tool_id));
+
+        if let Some(reason) = self.unretryable_tools.get(tool_id) {
+            let reason = SharedString::from(reason.clone());
+            return div()
+                .id(button_id)
+                .px_2()
+                .py_px()
+                .rounded(px(4.))
+                .text_xs()
+                .text_color(theme.foreground_muted)
+                .tooltip(move |window, cx| Tooltip::new(reason.clone()).build(window, cx))
+                .child("retry")
+                .into_any_element();
+        }
+
+        let retrying = self.retrying_tools.contains(tool_id);
+        let tool_id = tool_id.to_string();
+        div()
+            .id(button_id)
+            .px_2()
+            .py_px()
+            .rounded(px(4.))
+            .cursor_pointer()
+            .bg(theme.accent_red.opacity(0.2))
+            .text_xs()
+            .text_color(theme.accent_red)
+            .hover(|s| s.bg(theme.accent_red.opacity(0.3)))
+            .on_click(cx.listener(move |this, _event, _window, cx| {
+                // Don't also toggle the tool row underneath
+                cx.stop_propagation();
+                this.retry_tool(tool_id.clone(), cx);
+            }))
+            .child(if retrying { "retrying..." } else { "retry" })
+            .into_any_element()
+    }
+
+    /// Pretty-printed tool arguments, shown when `tool_display` includes args
+    pub fn render_tool_arguments(
+        &self,
+        id_prefix: &str,
+        tool_id: &str,
+        arguments: &serde_json::Value,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        let theme = &self.theme;
+        let args =
+            serde_json::to_string_pretty(arguments).unwrap_or_else(|_| arguments.to_string());
+        let copy_tool_id = tool_id.to_string();
+
+        div()
+            .id(SharedString::from(format!("{}-tool-args-{}", id_prefix, tool_id)))
+            .px_3()
+            .py_2()
+            .border_t_1()
+            .border_color(theme.border)
             .bg(theme.background)
-            .p_4()
-            .children(message_elements)
+            .max_h(px(200.))
+            .overflow_scroll()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .mb_1()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.foreground_muted)
+                            .font_weight(FontWeight::MEDIUM)
+                            .child("Arguments:"),
+                    )
+                    .child(
+                        div()
+                            .id(SharedString::from(format!(
+                                "{}-copy-args-{}",
+                                id_prefix, tool_id
+                            )))
+                            .px_2()
+                            .py_px()
+                            .rounded(px(4.))
+                            .cursor_pointer()
+                            .text_xs()
+                            .text_color(theme.foreground_dim)
+                            .hover(|s| {
+                                s.bg(theme.background_highlight).text_color(theme.accent_cyan)
+                            })
+                            .tooltip(|window, cx| {
+                                Tooltip::new("Copy arguments as JSON").build(window, cx)
+                            })
+                            .on_click(cx.listener(move |this, _event, _window, cx| {
+                                // Don't also toggle the tool row underneath
+                                cx.stop_propagation();
+                                this.copy_tool_args(&copy_tool_id, cx);
+                            }))
+                            .child("copy"),
+                    ),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .font_family(cx.theme().mono_font_family.clone())
+                    .text_color(theme.foreground_dim)
+                    .child(args),
+            )
+            .into_any_element()
+    }
+
+    /// Button that saves a tool call's full result to a file
+    pub fn render_tool_save_button(
+        &self,
+        id_prefix: &str,
+        tool_id: &str,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        let theme = &self.theme;
+        let tool_id = tool_id.to_string();
+
+        div()
+            .id(SharedString::from(format!("{}-save-{}", id_prefix, tool_id)))
+            .px_2()
+            .py_px()
+            .rounded(px(4.))
+            .cursor_pointer()
+            .text_xs()
+            .text_color(theme.foreground_dim)
+            .hover(|s| s.bg(theme.background_highlight).text_color(theme.accent_cyan))
+            .tooltip(|window, cx| Tooltip::new("Save full result to a file").build(window, cx))
+            .on_click(cx.listener(move |this, _event, _window, cx| {
+                // Don't also toggle the tool row underneath
+                cx.stop_propagation();
+                this.save_tool_result(tool_id.clone(), cx);
+            }))
+            .child("save")
+            .into_any_element()
+    }
+
+    /// Inline image for an image tool result; click opens it in the system viewer
+    fn render_tool_image(
+        &self,
+        tool_id: &str,
+        image: Arc<Image>,
+        reference: ImageReference,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        let theme = &self.theme;
+        let tool_id_owned = tool_id.to_string();
+        let open_image = image.clone();
+
+        div()
+            .id(SharedString::from(format!("tool-image-{}", tool_id)))
+            .px_3()
+            .py_2()
+            .border_t_1()
+            .border_color(theme.border)
+            .bg(theme.background)
+            .cursor_pointer()
+            .tooltip(|window, cx| Tooltip::new("Click to open").build(window, cx))
+            .on_click(cx.listener(move |this, _event, _window, cx| {
+                this.open_tool_image(&tool_id_owned, &open_image, &reference, cx);
+            }))
+            .child(
+                img(image)
+                    .max_w_full()
+                    .max_h(px(300.))
+                    .object_fit(ObjectFit::Contain),
+            )
+            .into_any_element()
+    }
+
+    /// Open an image result externally: URLs in the browser, inline data via a temp file
+    fn open_tool_image(
+        &mut self,
+        tool_id: &str,
+        image: &Image,
+        reference: &ImageReference,
+        cx: &mut Context<Self>,
+    ) {
+        match reference {
+            ImageReference::Url(url) => cx.open_url(url),
+            ImageReference::DataUri { .. } => {
+                let path = std::env::temp_dir()
+                    .join(format!("omniemployee-{}.{}", tool_id, reference.extension()));
+                match std::fs::write(&path, image.bytes()) {
+                    Ok(()) => cx.open_with_system(&path),
+                    Err(e) => {
+                        self.report_error(format!("⚠️ Could not open image: {}", e));
+                        cx.notify();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Copy text to the system clipboard
+    pub fn copy_to_clipboard(&mut self, text: String, cx: &mut Context<Self>) {
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+
+    /// Numbered source list under a reply; entries with a URL open it on click
+    fn render_citations(&self, msg: &ChatMessage, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+
+        div()
+            .mt_2()
+            .pt_1()
+            .border_t_1()
+            .border_color(theme.border)
+            .flex()
+            .flex_col()
+            .gap_1()
+            .children(msg.citations.iter().enumerate().map(|(i, citation)| {
+                let label = match (citation.title.is_empty(), citation.source.is_empty()) {
+                    (false, false) => format!("{} — {}", citation.title, citation.source),
+                    (false, true) => citation.title.clone(),
+                    (true, _) => citation.url.clone().unwrap_or_else(|| citation.source.clone()),
+                };
+                div()
+                    .id(SharedString::from(format!("citation-{}-{}", msg.id, i)))
+                    .flex()
+                    .gap_1()
+                    .text_xs()
+                    .text_color(theme.foreground_dim)
+                    .child(
+                        div()
+                            .text_color(theme.accent_cyan)
+                            .child(superscript_number(i + 1)),
+                    )
+                    .child(label)
+                    .when_some(citation.url.clone(), |el, url| {
+                        el.cursor_pointer()
+                            .hover(|s| s.text_color(theme.accent_cyan))
+                            .on_click(cx.listener(move |_this, _event, _window, cx| {
+                                cx.open_url(&url);
+                            }))
+                    })
+            }))
+    }
+
+    /// Collapsible "Reasoning" block holding a message's thinking tokens
+    fn render_reasoning_block(&self, msg: &ChatMessage, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+        let msg_id = msg.id.clone();
+        let expanded = msg.reasoning_expanded;
+
+        div()
+            .mb_2()
+            .rounded_md()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.background_secondary)
+            .overflow_hidden()
+            .child(
+                div()
+                    .id(SharedString::from(format!("reasoning-header-{}", msg.id)))
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_3()
+                    .py_1()
+                    .cursor_pointer()
+                    .text_xs()
+                    .text_color(theme.foreground_muted)
+                    .hover(|s| s.bg(theme.background_highlight))
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.toggle_reasoning(&msg_id, cx);
+                    }))
+                    .child(if expanded { "▼" } else { "▶" })
+                    .child(format!("{} Reasoning", self.icons().reasoning)),
+            )
+            .when(expanded, |el| {
+                el.child(
+                    div()
+                        .id(SharedString::from(format!("reasoning-content-{}", msg.id)))
+                        .px_3()
+                        .py_2()
+                        .border_t_1()
+                        .border_color(theme.border)
+                        .max_h(px(300.))
+                        .text_xs()
+                        .text_color(theme.foreground_dim)
+                        .whitespace_normal()
+                        .child(msg.reasoning.clone())
+                        .overflow_y_scrollbar(),
+                )
+            })
+    }
+
+    pub fn toggle_reasoning(&mut self, msg_id: &str, cx: &mut Context<Self>) {
+        if let Some(msg) = self.messages.iter_mut().find(|m| m.id == msg_id) {
+            msg.reasoning_expanded = !msg.reasoning_expanded;
+            cx.notify();
+        }
     }
 
     /// Toggle inline tool call expansion
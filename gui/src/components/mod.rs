@@ -3,7 +3,15 @@
 //! These modules extend the `App` struct with rendering methods.
 //! They are imported for their side effects (impl blocks).
 
+mod error_log;
 mod header;
 mod input;
+mod memory_browser;
 mod messages;
+mod modal;
+mod settings;
 mod sidebar;
+mod splash;
+mod toast;
+
+pub use messages::bind_message_keys;
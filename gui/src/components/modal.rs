@@ -0,0 +1,105 @@
+//! Modal overlay for OmniEmployee GUI
+//!
+//! The backdrop, panel and title bar shared by overlays such as the memory
+//! browser and the error log; each supplies its own controls and body.
+
+use gpui::{
+    div, px, Context, FontWeight, Hsla, InteractiveElement, IntoElement, ParentElement, Pixels,
+    SharedString, StatefulInteractiveElement, Styled,
+};
+
+use crate::app::App;
+
+impl App {
+    /// Centered panel over a dimmed backdrop. `controls` sit beside the ✕
+    /// button; `on_close` runs for it and for clicks on the backdrop.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_modal(
+        &self,
+        id: &'static str,
+        title: String,
+        color: Hsla,
+        width: Pixels,
+        controls: impl IntoElement,
+        body: impl IntoElement,
+        on_close: fn(&mut Self),
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = &self.theme;
+
+        div()
+            .id(SharedString::from(format!("{}-backdrop", id)))
+            .occlude()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.background_secondary.opacity(0.7))
+            .on_click(cx.listener(move |this, _event, _window, cx| {
+                on_close(this);
+                cx.notify();
+            }))
+            .child(
+                div()
+                    .id(id)
+                    .occlude()
+                    .w(width)
+                    .max_h(px(520.))
+                    .bg(theme.background_elevated)
+                    .border_1()
+                    .border_color(theme.border)
+                    .rounded(px(6.))
+                    .shadow_lg()
+                    .flex()
+                    .flex_col()
+                    // Keep clicks inside the panel from closing it
+                    .on_click(|_event, _window, cx| cx.stop_propagation())
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .justify_between()
+                            .px_3()
+                            .py_2()
+                            .border_b_1()
+                            .border_color(theme.border)
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .text_color(color)
+                                    .child(title),
+                            )
+                            .child(
+                                div()
+                                    .flex()
+                                    .items_center()
+                                    .gap_2()
+                                    .child(controls)
+                                    .child(
+                                        div()
+                                            .id(SharedString::from(format!("{}-close", id)))
+                                            .px_1()
+                                            .cursor_pointer()
+                                            .text_xs()
+                                            .text_color(theme.foreground_muted)
+                                            .hover(|s| s.text_color(theme.foreground))
+                                            .on_click(cx.listener(move |this, _event, _window, cx| {
+                                                on_close(this);
+                                                cx.notify();
+                                            }))
+                                            .child("✕"),
+                                    ),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .id(SharedString::from(format!("{}-list", id)))
+                            .flex_1()
+                            .overflow_y_scroll()
+                            .child(body),
+                    ),
+            )
+    }
+}
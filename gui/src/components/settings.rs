@@ -0,0 +1,258 @@
+//! Settings overlay for OmniEmployee GUI
+
+use gpui::{
+    anchored, deferred, div, px, Context, FontWeight, InteractiveElement, IntoElement,
+    ParentElement, SharedString, StatefulInteractiveElement, Styled, Window,
+};
+use gpui_component::input::Input;
+
+use crate::app::App;
+use crate::models::ChatMessage;
+
+impl App {
+    pub fn render_settings_panel(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+
+        deferred(
+            anchored().snap_to_window().child(
+                div()
+                    .id("settings-panel")
+                    .occlude()
+                    .w(px(320.))
+                    .p_3()
+                    .bg(theme.background_elevated)
+                    .border_1()
+                    .border_color(theme.border)
+                    .rounded(px(6.))
+                    .shadow_lg()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_sm()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(theme.foreground)
+                            .child("Connection Settings"),
+                    )
+                    .child(self.render_settings_label("Backend URL"))
+                    .child(Input::new(&self.settings_url_input))
+                    .child(self.render_settings_label("Request timeout (seconds)"))
+                    .child(Input::new(&self.settings_timeout_input))
+                    .child(div().h(px(1.)).w_full().bg(theme.border))
+                    .child(self.render_settings_toggle(
+                        "show_memory",
+                        "Show Memory panel",
+                        self.config.show_memory,
+                        cx,
+                    ))
+                    .child(self.render_settings_toggle(
+                        "show_knowledge",
+                        "Show Knowledge panel",
+                        self.config.show_knowledge,
+                        cx,
+                    ))
+                    .child(self.render_settings_toggle(
+                        "show_tools",
+                        "Show Tool Use panel",
+                        self.config.show_tool_use,
+                        cx,
+                    ))
+                    .child(self.render_settings_toggle(
+                        "render_markdown",
+                        "Render markdown",
+                        self.config.render_markdown,
+                        cx,
+                    ))
+                    .child(self.render_settings_toggle(
+                        "notify_on_complete",
+                        "Sound when a reply finishes in the background",
+                        self.config.notify_on_complete,
+                        cx,
+                    ))
+                    .child(self.render_settings_toggle(
+                        "clear_on_user_switch",
+                        "Clear chat when switching users",
+                        self.config.clear_on_user_switch,
+                        cx,
+                    ))
+                    .child(self.render_settings_toggle(
+                        "reduce_motion",
+                        "Reduce motion",
+                        self.config.reduce_motion,
+                        cx,
+                    ))
+                    .child(self.render_settings_toggle(
+                        "detect_tool_errors",
+                        "Flag tool results that look like errors",
+                        self.config.detect_tool_errors,
+                        cx,
+                    ))
+                    .child(self.render_settings_toggle(
+                        "ascii_mode",
+                        "Text icons instead of emoji",
+                        self.config.ascii_mode,
+                        cx,
+                    ))
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .mt_1()
+                            .child(
+                                div()
+                                    .id("settings-cancel")
+                                    .px_3()
+                                    .py_1()
+                                    .rounded(px(4.))
+                                    .cursor_pointer()
+                                    .text_sm()
+                                    .text_color(theme.foreground_dim)
+                                    .hover(|s| s.bg(theme.background_highlight))
+                                    .on_click(cx.listener(|this, _event, _window, cx| {
+                                        this.show_settings = false;
+                                        cx.notify();
+                                    }))
+                                    .child("Cancel"),
+                            )
+                            .child(
+                                div()
+                                    .id("settings-apply")
+                                    .px_3()
+                                    .py_1()
+                                    .rounded(px(4.))
+                                    .cursor_pointer()
+                                    .bg(theme.accent_cyan)
+                                    .text_sm()
+                                    .font_weight(FontWeight::MEDIUM)
+                                    .text_color(theme.background)
+                                    .hover(|s| s.bg(theme.accent_cyan.opacity(0.8)))
+                                    .on_click(cx.listener(|this, _event, _window, cx| {
+                                        this.apply_settings(cx);
+                                    }))
+                                    .child("Apply"),
+                            ),
+                    ),
+            ),
+        )
+    }
+
+    fn render_settings_label(&self, label: &'static str) -> impl IntoElement {
+        div()
+            .text_xs()
+            .text_color(self.theme.foreground_muted)
+            .child(label)
+    }
+
+    fn render_settings_toggle(
+        &self,
+        key: &'static str,
+        label: &'static str,
+        enabled: bool,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = &self.theme;
+
+        div()
+            .id(SharedString::from(format!("settings-toggle-{}", key)))
+            .flex()
+            .items_center()
+            .gap_2()
+            .py_1()
+            .cursor_pointer()
+            .on_click(cx.listener(move |this, _event, _window, cx| {
+                match key {
+                    "show_memory" => this.config.show_memory = !this.config.show_memory,
+                    "show_knowledge" => this.config.show_knowledge = !this.config.show_knowledge,
+                    "show_tools" => this.config.show_tool_use = !this.config.show_tool_use,
+                    "render_markdown" => {
+                        this.config.render_markdown = !this.config.render_markdown
+                    }
+                    "notify_on_complete" => {
+                        this.config.notify_on_complete = !this.config.notify_on_complete
+                    }
+                    "clear_on_user_switch" => {
+                        this.config.clear_on_user_switch = !this.config.clear_on_user_switch
+                    }
+                    "ascii_mode" => this.config.ascii_mode = !this.config.ascii_mode,
+                    "detect_tool_errors" => {
+                        this.config.detect_tool_errors = !this.config.detect_tool_errors
+                    }
+                    "reduce_motion" => this.config.reduce_motion = !this.config.reduce_motion,
+                    _ => {}
+                }
+                this.config.mark_explicit(key);
+                this.save_config();
+                cx.notify();
+            }))
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(if enabled {
+                        theme.accent_green
+                    } else {
+                        theme.foreground_muted
+                    })
+                    .child(if enabled { "☑" } else { "☐" }),
+            )
+            .child(div().text_sm().text_color(theme.foreground).child(label))
+    }
+
+    /// Open or close the settings overlay, filling the fields from the current client
+    pub fn toggle_settings(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.show_settings = !self.show_settings;
+        if self.show_settings {
+            let url = self.api_client.get_base_url().to_string();
+            let timeout = self.api_client.get_timeout_secs().to_string();
+            self.settings_url_input.update(cx, |state, cx| {
+                state.set_value(url, window, cx);
+            });
+            self.settings_timeout_input.update(cx, |state, cx| {
+                state.set_value(timeout, window, cx);
+            });
+        }
+        cx.notify();
+    }
+
+    /// Apply the URL/timeout fields, reconnecting if either changed
+    pub fn apply_settings(&mut self, cx: &mut Context<Self>) {
+        let url = self
+            .settings_url_input
+            .read(cx)
+            .value()
+            .trim()
+            .trim_end_matches('/')
+            .to_string();
+        let timeout_text = self.settings_timeout_input.read(cx).value().trim().to_string();
+
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            self.messages.push(ChatMessage::system(format!(
+                "Invalid backend URL: {}. It must start with http:// or https://",
+                url
+            )));
+            cx.notify();
+            return;
+        }
+        let timeout_secs = match timeout_text.parse::<u64>() {
+            Ok(secs) if secs > 0 => secs,
+            _ => {
+                self.messages.push(ChatMessage::system(format!(
+                    "Invalid timeout: {}. Use a positive number of seconds.",
+                    timeout_text
+                )));
+                cx.notify();
+                return;
+            }
+        };
+
+        self.show_settings = false;
+
+        let changed = url != self.api_client.get_base_url()
+            || timeout_secs != self.api_client.get_timeout_secs();
+        if changed {
+            self.connect_to_backend(url, timeout_secs, cx);
+        }
+        cx.notify();
+    }
+}
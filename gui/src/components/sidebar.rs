@@ -1,20 +1,27 @@
 //! Sidebar component for OmniEmployee GUI
 
+use std::rc::Rc;
+
 use gpui::{
     div, prelude::FluentBuilder, px, ClickEvent, Context, FontWeight, Hsla, InteractiveElement,
     IntoElement, ParentElement, SharedString, StatefulInteractiveElement, Styled, Window,
 };
+use gpui_component::scroll::ScrollableElement;
 use gpui_component::text::TextView;
 
 use crate::app::App;
-use crate::models::ToolStatus;
+use crate::api::ContextKnowledge;
+use crate::models::{
+    format_duration, group_consecutive_tools, KnowledgeSort, LiveToolCall, MessageSegment,
+    ToolFilter, ToolScope, ToolStatus,
+};
 
 impl App {
     pub fn render_sidebar(&self, cx: &Context<Self>) -> impl IntoElement {
         let theme = &self.theme;
 
         // Prepare memory items from current context (what was used for this query)
-        let memory_items: Vec<(String, String)> = self
+        let memory_items: Vec<(String, String, Option<Hsla>)> = self
             .current_context_memories
             .iter()
             .map(|m| {
@@ -24,22 +31,30 @@ impl App {
                 } else {
                     m.content.clone()
                 };
-                (content_preview, format!("E={:.2} • {}", m.energy, m.tier))
+                (content_preview, format!("E={:.2} • {}", m.energy, m.tier), None)
             })
             .collect();
 
         // Prepare knowledge items from current context (what was used for this query)
-        let knowledge_items: Vec<(String, String)> = self
-            .current_context_knowledge
-            .iter()
+        let knowledge_items: Vec<(String, String, Option<Hsla>)> = self
+            .sorted_knowledge()
+            .into_iter()
             .map(|k| {
                 (
                     format!("({}, {}, {})", k.subject, k.predicate, k.object),
                     format!("conf={:.2} • {}", k.confidence, k.source),
+                    Some(theme.confidence_color(k.confidence)),
                 )
             })
             .collect();
 
+        // Agent skills (refreshed on connect and model/provider changes)
+        let skill_items: Vec<(String, String, Option<Hsla>)> = self
+            .agent_skills
+            .iter()
+            .map(|skill| (skill.clone(), String::new(), None))
+            .collect();
+
         div()
             .w(px(300.))
             .h_full()
@@ -65,14 +80,19 @@ impl App {
                         div()
                             .text_xs()
                             .text_color(theme.foreground_muted)
-                            .child("Used for current query"),
+                            .child(format!(
+                                "Used for current query: {}",
+                                context_counts(
+                                    self.context_memory_count,
+                                    self.context_knowledge_count,
+                                )
+                            )),
                     ),
             )
             .child(
                 div()
                     .id("sidebar-panels")
                     .flex_1()
-                    .overflow_y_scroll()
                     .p_3()
                     .when(self.config.show_tool_use, |el| {
                         el.child(self.render_live_tool_panel(cx))
@@ -80,28 +100,98 @@ impl App {
                     .when(self.config.show_memory, |el| {
                         el.child(self.render_collapsible_panel(
                             "memory",
-                            "🧠",
+                            self.icons().memory,
                             "Memory",
                             self.memory_expanded,
                             theme.accent_purple,
                             memory_items,
+                            Some(self.memory_has_more),
                             cx,
                         ))
+                        .child(
+                            div()
+                                .id("browse-memories")
+                                .w_full()
+                                .mb_2()
+                                .px_1()
+                                .cursor_pointer()
+                                .text_xs()
+                                .text_color(theme.foreground_muted)
+                                .hover(|style| style.text_color(theme.accent_purple))
+                                .on_click(cx.listener(|this, _event, _window, cx| {
+                                    this.open_memory_browser(cx);
+                                }))
+                                .child("Browse all memories →"),
+                        )
                     })
                     .when(self.config.show_knowledge, |el| {
                         el.child(self.render_collapsible_panel(
                             "knowledge",
-                            "📚",
+                            self.icons().knowledge,
                             "Knowledge",
                             self.knowledge_expanded,
                             theme.accent_green,
                             knowledge_items,
+                            Some(self.knowledge_has_more),
                             cx,
                         ))
-                    }),
+                    })
+                    .child(self.render_collapsible_panel(
+                        "skills",
+                        self.icons().skills,
+                        "Skills",
+                        self.skills_expanded,
+                        theme.accent_cyan,
+                        skill_items,
+                        None,
+                        cx,
+                    ))
+                    .overflow_y_scrollbar(),
+            )
+            .when(
+                self.live_memory_stats.is_some() || self.live_knowledge_stats.is_some(),
+                |el| el.child(self.render_stats_strip()),
             )
     }
 
+    /// Compact live stats shown at the bottom of the sidebar
+    pub fn render_stats_strip(&self) -> impl IntoElement {
+        let theme = &self.theme;
+
+        let memory_text = self.live_memory_stats.as_ref().map(|stats| {
+            format!(
+                "{} L1 {} • L2 {} • L3 {}",
+                self.icons().memory,
+                stats.l1_count, stats.l2_vector_count, stats.l3_facts
+            )
+        });
+        let knowledge_text = self
+            .live_knowledge_stats
+            .as_ref()
+            .map(|stats| {
+                format!(
+                    "{} {} triples • {} subjects",
+                    self.icons().knowledge,
+                    stats.total_triples,
+                    stats.unique_subjects
+                )
+            });
+
+        div()
+            .px_4()
+            .py_2()
+            .border_t_1()
+            .border_color(theme.border)
+            .flex()
+            .flex_col()
+            .gap_1()
+            .text_xs()
+            .text_color(theme.foreground_dim)
+            .when_some(memory_text, |el, text| el.child(text))
+            .when_some(knowledge_text, |el, text| el.child(text))
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn render_collapsible_panel(
         &self,
         id: &'static str,
@@ -109,20 +199,38 @@ impl App {
         title: &'static str,
         expanded: bool,
         color: Hsla,
-        items: Vec<(String, String)>,
+        // (primary, secondary, primary color override)
+        items: Vec<(String, String, Option<Hsla>)>,
+        // Some(has_more) adds a "Load more" footer, disabled once nothing is left
+        load_more: Option<bool>,
         cx: &Context<Self>,
     ) -> impl IntoElement {
         let theme = &self.theme;
         let content_id = format!("{}-content", id);
+        let loading_more = self.loading_more;
+
+        let load_more_handler: Box<dyn Fn(&mut Self, &mut Context<Self>)> = match id {
+            "memory" => Box::new(Self::load_more_memories),
+            "knowledge" => Box::new(Self::load_more_knowledge),
+            _ => Box::new(|_, _| {}),
+        };
 
         let click_handler: Box<dyn Fn(&mut Self, &ClickEvent, &mut Window, &mut Context<Self>)> =
             match id {
                 "tools" => Box::new(Self::toggle_tools),
                 "memory" => Box::new(Self::toggle_memory),
                 "knowledge" => Box::new(Self::toggle_knowledge),
+                "skills" => Box::new(Self::toggle_skills),
                 _ => Box::new(|_, _, _, _| {}),
             };
 
+        // Knowledge rows are clickable; other panels stay read-only
+        let item_handler: Option<Rc<dyn Fn(&mut Self, usize, &mut Window, &mut Context<Self>)>> =
+            match id {
+                "knowledge" => Some(Rc::new(Self::ask_about_knowledge)),
+                _ => None,
+            };
+
         div()
             .w_full()
             .rounded_lg()
@@ -165,6 +273,9 @@ impl App {
                                     .child(title),
                             ),
                     )
+                    .when(id == "knowledge", |el| {
+                        el.child(div().flex_1()).child(self.render_knowledge_sort_control(cx))
+                    })
                     .child(
                         div()
                             .px_2()
@@ -177,52 +288,105 @@ impl App {
                     ),
             )
             .when(expanded, |el| {
-                el.child(
-                    div()
-                        .id(SharedString::from(content_id))
-                        .w_full()
-                        .max_h(px(200.))
-                        .overflow_y_scroll()
-                        .bg(theme.background_secondary)
-                        .when(items.is_empty(), |el| {
-                            el.child(
-                                div()
-                                    .w_full()
-                                    .px_3()
-                                    .py_2()
-                                    .text_sm()
-                                    .text_color(theme.foreground_muted)
-                                    .child("No items"),
-                            )
-                        })
-                        .children(items.iter().enumerate().map(|(i, (primary, secondary))| {
-                            let is_last = i == items.len() - 1;
+                let content = div()
+                    .id(SharedString::from(content_id))
+                    .w_full()
+                    .max_h(px(self.config.panel_max_height))
+                    .bg(theme.background_secondary)
+                    .when(items.is_empty(), |el| {
+                        el.child(
                             div()
                                 .w_full()
                                 .px_3()
                                 .py_2()
-                                .when(!is_last, |el| el.border_b_1().border_color(theme.border))
-                                .child(
-                                    div()
-                                        .text_sm()
-                                        .text_color(theme.foreground)
-                                        .overflow_hidden()
-                                        .child(primary.clone()),
-                                )
-                                .child(
+                                .text_sm()
+                                .text_color(theme.foreground_muted)
+                                .child("No items"),
+                        )
+                    })
+                    .children(items.iter().enumerate().map(|(i, item)| {
+                        let (primary, secondary, primary_color) = item;
+                        let is_last = i == items.len() - 1;
+                        div()
+                            .id(SharedString::from(format!("{}-item-{}", id, i)))
+                            .w_full()
+                            .px_3()
+                            .py_2()
+                            .when(!is_last, |el| el.border_b_1().border_color(theme.border))
+                            .when_some(item_handler.clone(), |el, handler| {
+                                el.cursor_pointer()
+                                    .hover(|style| style.bg(theme.background_highlight))
+                                    .on_click(cx.listener(move |this, _event, window, cx| {
+                                        handler(this, i, window, cx)
+                                    }))
+                            })
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(primary_color.unwrap_or(theme.foreground))
+                                    .overflow_hidden()
+                                    .child(primary.clone()),
+                            )
+                            .when(!secondary.is_empty(), |el| {
+                                el.child(
                                     div()
                                         .text_xs()
                                         .text_color(theme.foreground_dim)
                                         .child(secondary.clone()),
                                 )
-                        })),
-                )
+                            })
+                    }))
+                    .when_some(load_more, |el, has_more| {
+                        let enabled = has_more && !loading_more;
+                        el.child(
+                            div()
+                                .id(SharedString::from(format!("{}-load-more", id)))
+                                .w_full()
+                                .px_3()
+                                .py_1()
+                                .border_t_1()
+                                .border_color(theme.border)
+                                .text_xs()
+                                .text_color(if enabled { color } else { theme.foreground_muted })
+                                .when(enabled, |el| {
+                                    el.cursor_pointer()
+                                        .hover(|style| style.bg(theme.background_highlight))
+                                        .on_click(cx.listener(move |this, _event, _window, cx| {
+                                            load_more_handler(this, cx)
+                                        }))
+                                })
+                                .child(if loading_more {
+                                    "Loading..."
+                                } else if has_more {
+                                    "Load more"
+                                } else {
+                                    "No more items"
+                                }),
+                        )
+                    })
+                    .overflow_y_scrollbar();
+                el.child(self.expand_transition(
+                    SharedString::from(format!("{}-expand", id)),
+                    content,
+                    Some(self.config.panel_max_height),
+                ))
             })
     }
 
     pub fn render_live_tool_panel(&self, cx: &Context<Self>) -> impl IntoElement {
         let theme = &self.theme;
-        let has_tools = !self.live_tool_calls.is_empty();
+        let calls = self.panel_tool_calls();
+        let has_tools = !calls.is_empty();
+        let total = calls.len();
+        let visible: Vec<LiveToolCall> = calls
+            .into_iter()
+            .filter(|tc| self.tool_filter.matches(&tc.status))
+            .collect();
+        let count_label = if self.tool_filter == ToolFilter::All {
+            total.to_string()
+        } else {
+            format!("{} / {}", visible.len(), total)
+        };
 
         div()
             .w_full()
@@ -255,7 +419,7 @@ impl App {
                                     .text_color(theme.foreground_muted)
                                     .child(if self.tool_expanded { "▼" } else { "▶" }),
                             )
-                            .child(div().text_sm().child("🔧"))
+                            .child(div().text_sm().child(self.icons().tool))
                             .child(
                                 div()
                                     .text_sm()
@@ -266,21 +430,27 @@ impl App {
                     )
                     .child(
                         div()
-                            .px_2()
-                            .py_px()
-                            .rounded_full()
-                            .bg(theme.accent_orange.opacity(0.2))
-                            .text_xs()
-                            .text_color(theme.accent_orange)
-                            .child(self.live_tool_calls.len().to_string()),
+                            .flex()
+                            .items_center()
+                            .child(self.render_tool_scope_control(cx))
+                            .child(self.render_tool_filter_control(cx))
+                            .child(
+                                div()
+                                    .px_2()
+                                    .py_px()
+                                    .rounded_full()
+                                    .bg(theme.accent_orange.opacity(0.2))
+                                    .text_xs()
+                                    .text_color(theme.accent_orange)
+                                    .child(count_label),
+                            ),
                     ),
             )
             .when(self.tool_expanded, |el| {
                 let content = div()
                     .id("live-tools-content")
                     .w_full()
-                    .max_h(px(400.))
-                    .overflow_y_scroll()
+                    .max_h(px(self.config.tool_panel_max_height))
                     .bg(theme.background_secondary)
                     .when(!has_tools, |inner| {
                         inner.child(
@@ -293,95 +463,252 @@ impl App {
                                 .child("No tool calls yet"),
                         )
                     })
-                    .children(self.live_tool_calls.iter().enumerate().map(|(i, tc)| {
-                        let is_last = i == self.live_tool_calls.len() - 1;
-                        let status_icon = match tc.status {
-                            ToolStatus::Running => "⏳",
-                            ToolStatus::Completed => "✅",
-                            ToolStatus::Failed => "❌",
-                        };
-                        let status_color = match tc.status {
-                            ToolStatus::Running => theme.accent_yellow,
-                            ToolStatus::Completed => theme.accent_green,
-                            ToolStatus::Failed => theme.accent_red,
-                        };
-
-                        let result_content = tc.result.clone().unwrap_or_else(|| {
-                            if tc.status == ToolStatus::Running {
-                                "Running...".to_string()
-                            } else {
-                                "No result".to_string()
-                            }
-                        });
-
-                        let tool_id = tc.id.clone();
+                    .when(has_tools && visible.is_empty(), |inner| {
+                        inner.child(
+                            div()
+                                .w_full()
+                                .px_3()
+                                .py_2()
+                                .text_sm()
+                                .text_color(theme.foreground_muted)
+                                .child(format!("No {} tool calls", self.tool_filter.label())),
+                        )
+                    })
+                    .children(group_consecutive_tools(&visible).into_iter().map(|group| {
+                        let is_last = group.end == visible.len();
+                        let calls = &visible[group];
+                        if calls.len() == 1 {
+                            self.render_live_tool_row(&calls[0], is_last, cx).into_any_element()
+                        } else {
+                            self.render_live_tool_group(calls, is_last, cx).into_any_element()
+                        }
+                    }))
+                    .overflow_y_scrollbar();
+                el.child(self.expand_transition(
+                    "live-tools-expand",
+                    content,
+                    Some(self.config.tool_panel_max_height),
+                ))
+            })
+    }
+
+    /// Collapsible header for a run of same-named calls, e.g. "search ×4"
+    fn render_live_tool_group(
+        &self,
+        calls: &[LiveToolCall],
+        is_last: bool,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = &self.theme;
+        // Keyed by the first call so the state survives new calls joining the run
+        let group_id = calls[0].id.clone();
+        let expanded = self.expanded_tool_groups.contains(&group_id);
+        let any_with = |status: ToolStatus| calls.iter().any(|tc| tc.status == status);
+        let icons = self.icons();
+        let (status_icon, status_color) = if any_with(ToolStatus::Running) {
+            (icons.running, theme.accent_yellow)
+        } else if any_with(ToolStatus::Failed) {
+            (icons.failed, theme.accent_red)
+        } else {
+            (icons.ok, theme.accent_green)
+        };
+        let total_ms: u64 = calls.iter().filter_map(|tc| tc.duration_ms).sum();
 
+        div()
+            .w_full()
+            .when(!is_last, |inner| inner.border_b_1().border_color(theme.border))
+            .child(
+                div()
+                    .id(SharedString::from(format!("tool-group-{}", group_id)))
+                    .w_full()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_3()
+                    .py_2()
+                    .cursor_pointer()
+                    .hover(|style| style.bg(theme.background_highlight))
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        if !this.expanded_tool_groups.remove(&group_id) {
+                            this.expanded_tool_groups.insert(group_id.clone());
+                        }
+                        cx.notify();
+                    }))
+                    .child(
                         div()
-                            .w_full()
-                            .when(!is_last, |inner| inner.border_b_1().border_color(theme.border))
+                            .text_xs()
+                            .text_color(theme.foreground_muted)
+                            .child(if expanded { "▼" } else { "▶" }),
+                    )
+                    .child(div().text_sm().child(status_icon))
+                    .child(
+                        div()
+                            .text_sm()
+                            .font_weight(FontWeight::MEDIUM)
+                            .text_color(status_color)
+                            .child(format!("{} ×{}", calls[0].name, calls.len())),
+                    )
+                    .when(total_ms > 0, |el| {
+                        el.child(
+                            div()
+                                .text_xs()
+                                .text_color(theme.foreground_muted)
+                                .child(format!("({})", format_duration(total_ms))),
+                        )
+                    }),
+            )
+            .when(expanded, |el| {
+                el.child(
+                    div()
+                        .w_full()
+                        .pl_4()
+                        .border_t_1()
+                        .border_color(theme.border)
+                        .children(calls.iter().enumerate().map(|(i, tc)| {
+                            self.render_live_tool_row(tc, i + 1 == calls.len(), cx)
+                        })),
+                )
+            })
+    }
+
+    fn render_live_tool_row(
+        &self,
+        tc: &LiveToolCall,
+        is_last: bool,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = &self.theme;
+        let suspect = self.tool_result_suspect(&tc.status, tc.result.as_deref());
+        let status_icon = if suspect {
+            self.icons().warning
+        } else {
+            self.icons().tool_status(&tc.status)
+        };
+        let status_color = match tc.status {
+            _ if suspect => theme.accent_orange,
+            ToolStatus::Running => theme.accent_yellow,
+            ToolStatus::Completed => theme.accent_green,
+            ToolStatus::Failed => theme.accent_red,
+        };
+
+        let result_content = tc.result.clone().unwrap_or_else(|| {
+            if tc.status == ToolStatus::Running {
+                "Running...".to_string()
+            } else {
+                "No result".to_string()
+            }
+        });
+
+        let tool_id = tc.id.clone();
+        let expanded = self.live_tool_expanded(tc);
+        let compact = self.compact_live_tools() && !expanded;
+
+        div()
+            .w_full()
+            .when(!is_last, |inner| inner.border_b_1().border_color(theme.border))
+            .child(
+                div()
+                    .id(SharedString::from(format!("tool-{}", tc.id)))
+                    .w_full()
+                    .px_3()
+                    .when(compact, |el| el.py_1())
+                    .when(!compact, |el| el.py_2())
+                    .cursor_pointer()
+                    .hover(|style| style.bg(theme.background_highlight))
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        this.toggle_tool_call(tool_id.clone(), window, cx);
+                    }))
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
                             .child(
                                 div()
-                                    .id(SharedString::from(format!("tool-{}", tc.id)))
-                                    .w_full()
-                                    .px_3()
-                                    .py_2()
-                                    .cursor_pointer()
-                                    .hover(|style| style.bg(theme.background_highlight))
-                                    .on_click(cx.listener(move |this, _event, window, cx| {
-                                        this.toggle_tool_call(tool_id.clone(), window, cx);
-                                    }))
-                                    .child(
-                                        div()
-                                            .flex()
-                                            .items_center()
-                                            .gap_2()
-                                            .child(
-                                                div()
-                                                    .text_xs()
-                                                    .text_color(theme.foreground_muted)
-                                                    .child(if tc.expanded { "▼" } else { "▶" }),
-                                            )
-                                            .child(div().text_xs().child(status_icon))
-                                            .child(
-                                                div()
-                                                    .text_sm()
-                                                    .font_weight(FontWeight::MEDIUM)
-                                                    .text_color(status_color)
-                                                    .child(tc.name.clone()),
-                                            ),
-                                    )
-                                    .when(tc.expanded, |inner| {
-                                        let result_view = TextView::markdown(
-                                            SharedString::from(format!("tool-result-{}", tc.id)),
-                                            result_content.clone(),
-                                        );
-                                        let result_container_id =
-                                            format!("tool-result-container-{}", tc.id);
-
-                                        inner.child(
-                                            div()
-                                                .id(SharedString::from(result_container_id))
-                                                .mt_2()
-                                                .p_2()
-                                                .rounded(px(4.))
-                                                .bg(theme.background)
-                                                .max_h(px(300.))
-                                                .overflow_y_scroll()
-                                                .child(
-                                                    div()
-                                                        .text_xs()
-                                                        .text_color(theme.foreground_muted)
-                                                        .font_weight(FontWeight::MEDIUM)
-                                                        .mb_1()
-                                                        .child("Result:"),
-                                                )
-                                                .child(div().text_xs().child(result_view)),
-                                        )
-                                    }),
+                                    .text_xs()
+                                    .text_color(theme.foreground_muted)
+                                    .child(if expanded { "▼" } else { "▶" }),
                             )
-                    }));
-                el.child(content)
-            })
+                            .child(self.render_tool_status_icon(
+                                &tc.id,
+                                status_icon,
+                                tc.status == ToolStatus::Running,
+                            ))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .font_weight(FontWeight::MEDIUM)
+                                    .text_color(status_color)
+                                    .child(tc.name.clone()),
+                            )
+                            .when_some(tc.duration_ms.filter(|_| !compact), |el, ms| {
+                                el.child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(theme.foreground_muted)
+                                        .child(format!("({})", format_duration(ms))),
+                                )
+                            })
+                            .when(tc.status == ToolStatus::Failed, |el| {
+                                el.child(self.render_tool_retry_button("live", &tc.id, cx))
+                            })
+                            .when(expanded && tc.result.is_some(), |el| {
+                                el.child(self.render_tool_save_button("live", &tc.id, cx))
+                            }),
+                    )
+                    .when(expanded && self.config.tool_display.shows_args(), |inner| {
+                        inner.child(
+                            div()
+                                .mt_2()
+                                .rounded(px(4.))
+                                .overflow_hidden()
+                                .child(self.render_tool_arguments("live", &tc.id, &tc.arguments, cx)),
+                        )
+                    })
+                    .when(expanded && self.config.tool_display.shows_result(), |inner| {
+                        let result_view = TextView::markdown(
+                            SharedString::from(format!("tool-result-{}", tc.id)),
+                            result_content.clone(),
+                        );
+                        let result_container_id = format!("tool-result-container-{}", tc.id);
+
+                        inner.child(
+                            div()
+                                .id(SharedString::from(result_container_id))
+                                .mt_2()
+                                .p_2()
+                                .rounded(px(4.))
+                                .bg(theme.background)
+                                .when(suspect, |el| {
+                                    el.border_1()
+                                        .border_color(theme.accent_red)
+                                        .text_color(theme.accent_red)
+                                })
+                                .max_h(px(300.))
+                                // Scroll both ways so unbroken lines (e.g. JSON) aren't clipped
+                                .overflow_scroll()
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(theme.foreground_muted)
+                                        .font_weight(FontWeight::MEDIUM)
+                                        .mb_1()
+                                        .child(if suspect {
+                                            "Result (looks like an error):"
+                                        } else {
+                                            "Result:"
+                                        }),
+                                )
+                                .child(
+                                    div()
+                                        .w_full()
+                                        .whitespace_normal()
+                                        .text_xs()
+                                        .child(result_view),
+                                ),
+                        )
+                    }),
+            )
     }
 
     pub fn toggle_memory(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
@@ -399,20 +726,218 @@ impl App {
         cx.notify();
     }
 
+    pub fn toggle_skills(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        self.skills_expanded = !self.skills_expanded;
+        cx.notify();
+    }
+
     pub fn toggle_tools(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
         self.tool_expanded = !self.tool_expanded;
         cx.notify();
     }
 
+    /// Knowledge panel rows in the selected order (rows are addressed by this index)
+    pub fn sorted_knowledge(&self) -> Vec<&ContextKnowledge> {
+        let mut knowledge: Vec<&ContextKnowledge> = self.current_context_knowledge.iter().collect();
+        match self.knowledge_sort {
+            KnowledgeSort::Confidence => {
+                knowledge.sort_by(|a, b| b.confidence.total_cmp(&a.confidence))
+            }
+            KnowledgeSort::Subject => knowledge.sort_by_key(|k| k.subject.to_lowercase()),
+            KnowledgeSort::Source => knowledge.sort_by(|a, b| a.source.cmp(&b.source)),
+        }
+        knowledge
+    }
+
+    /// "Sort: …" chip in the Knowledge panel header; click cycles the order
+    fn render_knowledge_sort_control(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+
+        div()
+            .id("knowledge-sort")
+            .mr_2()
+            .px_1()
+            .rounded(px(4.))
+            .text_xs()
+            .text_color(theme.foreground_muted)
+            .hover(|style| style.text_color(theme.accent_green))
+            .on_click(cx.listener(|this, _event, _window, cx| {
+                // Don't also collapse the panel
+                cx.stop_propagation();
+                this.knowledge_sort = this.knowledge_sort.next();
+                cx.notify();
+            }))
+            .child(format!("Sort: {}", self.knowledge_sort.label()))
+    }
+
+    /// Calls the Tool Use panel lists: this response's live calls, or every call
+    /// in the conversation (including sessions restored from disk)
+    fn panel_tool_calls(&self) -> Vec<LiveToolCall> {
+        if self.tool_scope == ToolScope::Response {
+            return self.live_tool_calls.clone();
+        }
+
+        let mut calls = Vec::new();
+        for msg in &self.messages {
+            let before = calls.len();
+            for seg in &msg.segments {
+                if let MessageSegment::ToolCall(tc) = seg {
+                    calls.push(LiveToolCall {
+                        status: tc.status.clone(),
+                        duration_ms: tc.duration_ms,
+                        ..self.session_tool_row(tc.id.clone(), &tc.name, &tc.arguments, &tc.result)
+                    });
+                }
+            }
+            // Saved sessions keep only the summary list, without ids
+            if calls.len() == before {
+                for (i, tc) in msg.tool_calls.iter().enumerate() {
+                    let id = format!("{}-tool-{}", msg.id, i);
+                    calls.push(LiveToolCall {
+                        status: if tc.success {
+                            ToolStatus::Completed
+                        } else {
+                            ToolStatus::Failed
+                        },
+                        ..self.session_tool_row(id, &tc.name, &tc.arguments, &tc.result)
+                    });
+                }
+            }
+        }
+        calls
+    }
+
+    fn session_tool_row(
+        &self,
+        id: String,
+        name: &str,
+        arguments: &serde_json::Value,
+        result: &Option<String>,
+    ) -> LiveToolCall {
+        LiveToolCall {
+            expanded: self.session_tool_expanded.contains(&id),
+            id,
+            name: name.to_string(),
+            arguments: arguments.clone(),
+            result: result.clone(),
+            status: ToolStatus::Completed,
+            // Expansion comes from `session_tool_expanded`, never compact streaming
+            pinned: true,
+            started_at: std::time::Instant::now(),
+            duration_ms: None,
+        }
+    }
+
+    /// Scope chip in the Tool Use panel header; click switches between the
+    /// latest response and the whole session
+    fn render_tool_scope_control(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+
+        div()
+            .id("tool-scope")
+            .mr_2()
+            .px_1()
+            .rounded(px(4.))
+            .text_xs()
+            .text_color(theme.foreground_muted)
+            .hover(|style| style.text_color(theme.accent_orange))
+            .on_click(cx.listener(|this, _event, _window, cx| {
+                // Don't also collapse the panel
+                cx.stop_propagation();
+                this.tool_scope = this.tool_scope.next();
+                cx.notify();
+            }))
+            .child(self.tool_scope.label())
+    }
+
+    /// "Show: …" chip in the Tool Use panel header; click cycles the filter
+    fn render_tool_filter_control(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+
+        div()
+            .id("tool-filter")
+            .mr_2()
+            .px_1()
+            .rounded(px(4.))
+            .text_xs()
+            .text_color(if self.tool_filter == ToolFilter::All {
+                theme.foreground_muted
+            } else {
+                theme.accent_orange
+            })
+            .hover(|style| style.text_color(theme.accent_orange))
+            .on_click(cx.listener(|this, _event, _window, cx| {
+                // Don't also collapse the panel
+                cx.stop_propagation();
+                this.tool_filter = this.tool_filter.next();
+                cx.notify();
+            }))
+            .child(format!("Show: {}", self.tool_filter.label()))
+    }
+
+    /// Put a follow-up question about a knowledge triple into the input
+    pub fn ask_about_knowledge(
+        &mut self,
+        index: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(k) = self.sorted_knowledge().get(index).copied() else {
+            return;
+        };
+        let value = format!("Tell me more about {} {} {}", k.subject, k.predicate, k.object);
+        self.input_state.update(cx, |state, cx| {
+            state.set_value(value, window, cx);
+            state.focus(window, cx);
+        });
+    }
+
     pub fn toggle_tool_call(
         &mut self,
         tool_id: String,
         _window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if let Some(tc) = self.live_tool_calls.iter_mut().find(|t| t.id == tool_id) {
-            tc.expanded = !tc.expanded;
+        if self.tool_scope == ToolScope::Session {
+            if !self.session_tool_expanded.remove(&tool_id) {
+                self.session_tool_expanded.insert(tool_id);
+            }
             cx.notify();
+            return;
+        }
+        let Some(index) = self.live_tool_calls.iter().position(|t| t.id == tool_id) else {
+            return;
+        };
+        let expanded = self.live_tool_expanded(&self.live_tool_calls[index]);
+        let tc = &mut self.live_tool_calls[index];
+        tc.expanded = !expanded;
+        tc.pinned = true;
+        cx.notify();
+    }
+
+    /// Whether live tool rows are currently squeezed to one line each
+    fn compact_live_tools(&self) -> bool {
+        self.is_loading && self.config.compact_during_stream
+    }
+
+    /// Effective expansion of a live tool row: while compacting, only the latest
+    /// call is open unless the user toggled the row themselves
+    fn live_tool_expanded(&self, tc: &LiveToolCall) -> bool {
+        if self.compact_live_tools() && !tc.pinned {
+            self.live_tool_calls.last().is_some_and(|last| last.id == tc.id)
+        } else {
+            tc.expanded
         }
     }
 }
+
+/// "3 memories, 1 fact" for the sidebar header
+fn context_counts(memories: usize, facts: usize) -> String {
+    format!(
+        "{} {}, {} {}",
+        memories,
+        if memories == 1 { "memory" } else { "memories" },
+        facts,
+        if facts == 1 { "fact" } else { "facts" }
+    )
+}
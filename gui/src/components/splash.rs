@@ -0,0 +1,141 @@
+//! Splash screen for OmniEmployee GUI
+//!
+//! Covers the window while the first connection attempt runs, so the app
+//! doesn't open onto an empty chat. Cancelling it leaves a manual-connect
+//! screen instead.
+
+use gpui::{
+    div, prelude::FluentBuilder, px, AnimationExt, Context, Div, FontWeight, Hsla,
+    InteractiveElement, IntoElement, ParentElement, Stateful, StatefulInteractiveElement, Styled,
+};
+
+use crate::app::App;
+use crate::models::{ChatMessage, ConnectionStatus};
+use crate::theme::pulse_animation;
+
+impl App {
+    pub fn render_splash(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+        let connecting = self.connection_status == ConnectionStatus::Connecting;
+
+        div()
+            .size_full()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.background)
+            .text_color(theme.foreground)
+            .child(
+                div()
+                    .w(px(360.))
+                    .p_6()
+                    .flex()
+                    .flex_col()
+                    .items_center()
+                    .gap_3()
+                    .bg(theme.background_elevated)
+                    .border_1()
+                    .border_color(theme.border)
+                    .rounded(px(8.))
+                    .shadow_lg()
+                    .child(div().text_3xl().child(self.icons().bot))
+                    .child(
+                        div()
+                            .text_xl()
+                            .font_weight(FontWeight::BOLD)
+                            .text_color(theme.accent_cyan)
+                            .child("OmniEmployee"),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(theme.foreground_dim)
+                            .child(if connecting {
+                                "Connecting to backend"
+                            } else {
+                                "Not connected"
+                            }),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.foreground_muted)
+                            .child(self.api_client.get_base_url().to_string()),
+                    )
+                    .when(connecting, |el| {
+                        el.child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.accent_cyan)
+                                .child("● ● ●")
+                                .with_animation("splash-spinner", pulse_animation(), |el, delta| {
+                                    el.opacity(delta)
+                                }),
+                        )
+                        .child(
+                            self.splash_button("splash-cancel", "Cancel", theme.foreground_dim)
+                                .on_click(cx.listener(|this, _event, _window, cx| {
+                                    this.cancel_connect(cx);
+                                })),
+                        )
+                    })
+                    .when(!connecting, |el| {
+                        el.child(
+                            div()
+                                .flex()
+                                .gap_2()
+                                .child(
+                                    self.splash_button("splash-connect", "Connect", theme.accent_cyan)
+                                        .on_click(cx.listener(|this, _event, _window, cx| {
+                                            this.connection_status = ConnectionStatus::Connecting;
+                                            this.initialize(cx);
+                                            cx.notify();
+                                        })),
+                                )
+                                .child(
+                                    self.splash_button(
+                                        "splash-skip",
+                                        "Continue offline",
+                                        theme.foreground_dim,
+                                    )
+                                    .on_click(cx.listener(|this, _event, _window, cx| {
+                                        this.show_splash = false;
+                                        cx.notify();
+                                    })),
+                                ),
+                        )
+                    }),
+            )
+    }
+
+    /// Abandon the in-flight connect attempt (and any pending retry)
+    pub fn cancel_connect(&mut self, cx: &mut Context<Self>) {
+        self.connect_task = None;
+        // A result already on its way must not flip the status back
+        self.connect_generation += 1;
+        self.cancel_reconnect();
+        self.connection_status = ConnectionStatus::Disconnected;
+        self.messages.clear();
+        self.messages.push(ChatMessage::system(format!(
+            "Not connected to {}. Use /reconnect to try again, or /profile to pick another backend.",
+            self.api_client.get_base_url()
+        )));
+        cx.notify();
+    }
+
+    fn splash_button(&self, id: &'static str, label: &'static str, color: Hsla) -> Stateful<Div> {
+        let theme = &self.theme;
+        div()
+            .id(id)
+            .px_3()
+            .py_1()
+            .rounded(px(4.))
+            .border_1()
+            .border_color(theme.border)
+            .cursor_pointer()
+            .text_sm()
+            .text_color(color)
+            .hover(|s| s.bg(theme.background_highlight))
+            .child(label)
+    }
+}
@@ -0,0 +1,107 @@
+//! Toast notifications for OmniEmployee GUI
+
+use gpui::{
+    div, prelude::FluentBuilder, px, Context, FontWeight, InteractiveElement, IntoElement,
+    ParentElement, StatefulInteractiveElement, Styled,
+};
+
+use crate::app::App;
+use crate::models::KnowledgeToast;
+
+/// Rows listed before collapsing the rest into "+N more"
+const MAX_TOAST_ROWS: usize = 5;
+
+impl App {
+    /// "Knowledge updated" toast listing added triples and old → new changes
+    pub fn render_knowledge_toast(
+        &self,
+        toast: &KnowledgeToast,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = &self.theme;
+
+        let mut rows: Vec<(&'static str, String)> = toast
+            .updated
+            .iter()
+            .map(|k| {
+                let old = k.old_object.as_deref().unwrap_or("?");
+                ("~", format!("{} {}: {} → {}", k.subject, k.predicate, old, k.object))
+            })
+            .chain(
+                toast
+                    .added
+                    .iter()
+                    .map(|k| ("+", format!("{} {} {}", k.subject, k.predicate, k.object))),
+            )
+            .collect();
+        let hidden = rows.len().saturating_sub(MAX_TOAST_ROWS);
+        rows.truncate(MAX_TOAST_ROWS);
+
+        div()
+            .id("knowledge-toast")
+            .occlude()
+            .absolute()
+            .top(px(64.))
+            .right(px(16.))
+            .w(px(320.))
+            .p_3()
+            .bg(theme.background_elevated)
+            .border_1()
+            .border_color(theme.accent_green)
+            .rounded(px(6.))
+            .shadow_lg()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .child(
+                div()
+                    .flex()
+                    .justify_between()
+                    .child(
+                        div()
+                            .text_sm()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(theme.accent_green)
+                            .child(format!("{} Knowledge updated", self.icons().knowledge)),
+                    )
+                    .child(
+                        div()
+                            .id("knowledge-toast-close")
+                            .px_1()
+                            .cursor_pointer()
+                            .text_xs()
+                            .text_color(theme.foreground_muted)
+                            .hover(|s| s.text_color(theme.foreground))
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                this.knowledge_toast = None;
+                                cx.notify();
+                            }))
+                            .child("✕"),
+                    ),
+            )
+            .children(rows.into_iter().map(|(marker, text)| {
+                div()
+                    .flex()
+                    .gap_2()
+                    .text_xs()
+                    .child(
+                        div()
+                            .text_color(if marker == "+" {
+                                theme.accent_green
+                            } else {
+                                theme.accent_yellow
+                            })
+                            .child(marker),
+                    )
+                    .child(div().text_color(theme.foreground_dim).child(text))
+            }))
+            .when(hidden > 0, |el| {
+                el.child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.foreground_muted)
+                        .child(format!("+{} more", hidden)),
+                )
+            })
+    }
+}
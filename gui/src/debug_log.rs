@@ -0,0 +1,76 @@
+//! Optional client-side debug log of API traffic
+//!
+//! Disabled by default; enable with `OMNIEMPLOYEE_DEBUG=1` or
+//! `/config debug true`. Lines go to `<config>/omniemployee/logs/api.log`,
+//! which is rotated to `api.log.1` once it grows past `MAX_LOG_BYTES`.
+//! Callers are the blocking API methods, which already run off the render
+//! thread.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::models::app_config_dir;
+
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+const MAX_ENTRY_CHARS: usize = 500;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Serializes writes and rotation across background tasks
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Enable logging if the config asks for it or `OMNIEMPLOYEE_DEBUG` is set
+pub fn init(config_enabled: bool) {
+    let env_enabled = std::env::var("OMNIEMPLOYEE_DEBUG")
+        .map(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    set_enabled(config_enabled || env_enabled);
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Where the log is written, if there is a config directory
+pub fn log_path() -> Option<PathBuf> {
+    app_config_dir().map(|dir| dir.join("logs").join("api.log"))
+}
+
+/// Append a line; the message is only built when logging is enabled
+pub fn log(message: impl FnOnce() -> String) {
+    if !is_enabled() {
+        return;
+    }
+    let Some(path) = log_path() else {
+        return;
+    };
+    let line = format!("{} {}\n", chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"), message());
+
+    // Logging must never take the app down, so failures are ignored
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if std::fs::metadata(&path).map(|m| m.len() > MAX_LOG_BYTES).unwrap_or(false) {
+        let _ = std::fs::rename(&path, path.with_extension("log.1"));
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+/// Shorten a body for the log (char-safe)
+pub fn truncate(text: &str) -> String {
+    if text.chars().count() > MAX_ENTRY_CHARS {
+        let head: String = text.chars().take(MAX_ENTRY_CHARS).collect();
+        format!("{}... ({} chars)", head, text.chars().count())
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,109 @@
+//! Conversation export for OmniEmployee GUI
+//!
+//! `/export json <path>` writes the whole conversation as plain JSON so it can
+//! be analyzed by scripts. The DTOs below are decoupled from the GPUI-side
+//! message structs and keep tool results untruncated.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::app::App;
+use crate::models::{ChatMessage, MessageSegment, ToolStatus};
+
+/// A conversation as exported to JSON
+#[derive(Debug, Serialize)]
+pub struct ExportedConversation {
+    pub session_id: String,
+    pub user_id: String,
+    pub exported_at: String,
+    pub messages: Vec<ExportedMessage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedMessage {
+    pub role: String,
+    pub timestamp: String,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    pub tool_calls: Vec<ExportedToolCall>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedToolCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+    pub result: Option<String>,
+    pub status: String,
+}
+
+impl ExportedMessage {
+    fn from_message(msg: &ChatMessage) -> Self {
+        // Inline segments carry status and results; the summary list is the fallback
+        let mut tool_calls: Vec<ExportedToolCall> = msg
+            .segments
+            .iter()
+            .filter_map(|seg| match seg {
+                MessageSegment::ToolCall(tc) => Some(ExportedToolCall {
+                    name: tc.name.clone(),
+                    arguments: tc.arguments.clone(),
+                    result: tc.result.clone(),
+                    status: match tc.status {
+                        ToolStatus::Running => "running",
+                        ToolStatus::Completed => "completed",
+                        ToolStatus::Failed => "failed",
+                    }
+                    .to_string(),
+                }),
+                MessageSegment::Text(_) => None,
+            })
+            .collect();
+        if tool_calls.is_empty() {
+            tool_calls = msg
+                .tool_calls
+                .iter()
+                .map(|tc| ExportedToolCall {
+                    name: tc.name.clone(),
+                    arguments: tc.arguments.clone(),
+                    result: tc.result.clone(),
+                    status: if tc.success { "completed" } else { "failed" }.to_string(),
+                })
+                .collect();
+        }
+
+        Self {
+            role: msg.role.as_str().to_string(),
+            timestamp: msg.timestamp.to_rfc3339(),
+            content: msg.content.clone(),
+            reasoning: (!msg.reasoning.is_empty()).then(|| msg.reasoning.clone()),
+            tool_calls,
+        }
+    }
+}
+
+/// Expand a leading `~/` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+impl App {
+    /// Serialize the conversation (excluding a reply still streaming) as pretty JSON
+    pub fn export_conversation_json(&self) -> String {
+        let conversation = ExportedConversation {
+            session_id: self.session_id.clone(),
+            user_id: self.current_user_id.clone(),
+            exported_at: chrono::Local::now().to_rfc3339(),
+            messages: self
+                .messages
+                .iter()
+                .filter(|m| self.streaming_message_id.as_ref() != Some(&m.id))
+                .map(ExportedMessage::from_message)
+                .collect(),
+        };
+        serde_json::to_string_pretty(&conversation).unwrap_or_else(|_| "{}".to_string())
+    }
+}
@@ -1,18 +1,147 @@
 //! Command and message handling for OmniEmployee GUI
 
-use gpui::{AppContext as _, AsyncApp, Context};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use base64::Engine as _;
+use gpui::{AppContext as _, AsyncApp, Context, Image, ImageFormat};
+use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::api::{StreamEvent, ToolCall};
+use crate::api::{ChatRequest, KnowledgeChange, RateLimited, StreamEvent, ToolCall};
 use crate::app::App;
-use crate::models::{ChatMessage, Command, ConnectionStatus, InlineToolCall, LiveToolCall, MessageRole, ToolStatus};
+use crate::debug_log;
+use crate::export;
+use crate::models::{
+    detect_image_reference, ChatMessage, Citation, Command, ConnectionStatus, Density,
+    ImageReference, InlineToolCall, KnowledgeToast, LiveToolCall, MessageRole, MessageSegment,
+    Profile, TimestampFormat, ToolDisplayMode, ToolImage, ToolStatus, MAX_MESSAGE_WIDTH,
+    MAX_PANEL_HEIGHT, MIN_MESSAGE_WIDTH, MIN_PANEL_HEIGHT,
+};
+use crate::sessions;
+use crate::theme::{apply_scrollbar_colors, parse_hex_color, ACCENT_KEYS};
+
+/// Items fetched per "Load more" click in the sidebar panels
+const SIDEBAR_PAGE_SIZE: usize = 20;
+
+/// Most recent errors kept for /errors
+const ERROR_LOG_LIMIT: usize = 200;
+
+/// Keys listed by a bare `/config`, in help order (accent keys follow)
+const CONFIG_KEYS: [&str; 21] = [
+    "show_memory",
+    "show_knowledge",
+    "show_tools",
+    "render_markdown",
+    "density",
+    "timestamp_format",
+    "clock_12h",
+    "tool_default",
+    "compact_during_stream",
+    "ascii_mode",
+    "detect_tool_errors",
+    "reduce_motion",
+    "message_width",
+    "panel_max_height",
+    "tool_panel_max_height",
+    "clear_on_user_switch",
+    "notify_on_complete",
+    "max_input_chars",
+    "stats_interval",
+    "context_warn_threshold",
+    "debug",
+];
+
+/// Play a short system sound; silently does nothing where no player is available
+fn play_completion_sound() {
+    let (program, args): (&str, &[&str]) = if cfg!(target_os = "macos") {
+        ("afplay", &["/System/Library/Sounds/Glass.aiff"])
+    } else if cfg!(target_os = "linux") {
+        ("canberra-gtk-play", &["-i", "complete"])
+    } else if cfg!(target_os = "windows") {
+        ("powershell", &["-NoProfile", "-Command", "[System.Media.SystemSounds]::Asterisk.Play()"])
+    } else {
+        return;
+    };
+    let _ = std::process::Command::new(program)
+        .args(args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+}
+
+/// Map a MIME type (`image/png`) or file extension (`png`) to an image format
+fn image_format(kind: &str) -> Option<ImageFormat> {
+    let kind = kind.split(';').next().unwrap_or_default().trim().to_lowercase();
+    match kind.trim_start_matches("image/") {
+        "png" => Some(ImageFormat::Png),
+        "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+        "gif" => Some(ImageFormat::Gif),
+        "webp" => Some(ImageFormat::Webp),
+        "svg" | "svg+xml" => Some(ImageFormat::Svg),
+        "bmp" => Some(ImageFormat::Bmp),
+        _ => None,
+    }
+}
+
+/// Chat notice for a finished /clear; `Err` holds an error to report. The view
+/// is cleared either way, so a backend failure says the agent may still remember.
+fn clear_notice(result: anyhow::Result<()>) -> Result<String, String> {
+    result
+        .map(|()| "Conversation cleared.".to_string())
+        .map_err(|e| {
+            format!(
+                "⚠️ Cleared locally, but the backend could not clear its history: {}\n\
+                The assistant may still remember this conversation. Run /clear again to retry.",
+                e
+            )
+        })
+}
+
+/// Pretty JSON in a fenced block, for the `raw` variants of stats commands
+fn raw_json_block<T: serde::Serialize>(value: &T) -> String {
+    let json = serde_json::to_string_pretty(value).unwrap_or_else(|e| e.to_string());
+    format!("```json\n{}\n```", json)
+}
+
+/// Injected tool output, in the order it must be stripped
+static TOOL_OUTPUT_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        // Tool header line, optional italic progress line, then its fenced output
+        r"(?m)^[ \t]*🔧[ \t]*\*\*\w+\*\*[ \t]*\n(?:[ \t]*_[^_\n]*\.\.\._[ \t]*\n)?[ \t]*```[^\n]*\n(?s:.*?)\n[ \t]*```[ \t]*$",
+        // Tool header line followed by an italic progress line
+        r"(?m)^[ \t]*🔧[ \t]*\*\*\w+\*\*[ \t]*\n[ \t]*_[^_\n]*\.\.\._[ \t]*$",
+        // Bare tool header on its own line
+        r"(?m)^[ \t]*🔧[ \t]*\*\*\w+\*\*[ \t]*$",
+        r"\[Summarized from \d+ chars\]",
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).expect("valid tool output pattern"))
+    .collect()
+});
+
+static EMPTY_FENCE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"```[ \t]*\n?[ \t]*```").expect("valid empty fence pattern"));
 
 impl App {
     /// Send a message and process the response via streaming
     pub fn send_message_with_text(&mut self, text: String, cx: &mut Context<Self>) {
+        self.send_message_as(text, None, cx);
+    }
+
+    /// Send a message, optionally answered with another user's memory for this
+    /// one request (the current user stays selected)
+    pub fn send_message_as(
+        &mut self,
+        text: String,
+        user_override: Option<String>,
+        cx: &mut Context<Self>,
+    ) {
         let text = text.trim().to_string();
 
-        if text.is_empty() || self.is_loading {
+        if text.is_empty() || self.is_loading || !self.check_input_limit(&text) {
             return;
         }
 
@@ -22,95 +151,182 @@ impl App {
             return;
         }
 
+        // The first message names the session
+        if self.session_meta.title.is_none() {
+            self.session_meta.title = Some(sessions::session_title(&text));
+        }
+
         // Add user message
         self.messages.push(ChatMessage::user(&text));
+        self.last_query = text.clone();
         self.is_loading = true;
+        self.stop_after_step = false;
         self.current_tool_calls.clear();
         self.live_tool_calls.clear();
+        self.expanded_tool_groups.clear();
 
         // Create streaming assistant message placeholder with segments
-        let stream_msg = ChatMessage::assistant_streaming();
+        let mut stream_msg = ChatMessage::assistant_streaming();
+        stream_msg.answered_as = user_override.clone();
         let stream_msg_id = stream_msg.id.clone();
-        self.streaming_message_id = Some(stream_msg_id.clone());
+        let finish_msg_id = stream_msg_id.clone();
+        self.streaming_message_id = Some(stream_msg_id);
         self.streaming_content.clear();
+        self.last_chunk_at = None;
+        self.stream_started_at = None;
         self.messages.push(stream_msg);
+        self.stick_to_bottom = true;
+        self.unseen_stream_content = false;
+        self.scroll_message_list_to_end();
         cx.notify();
 
         // Send to API with streaming
         let api_client = self.api_client.clone();
-        let session_id = self.session_id.clone();
-        let message = text.clone();
+        let request = ChatRequest {
+            message: text.clone(),
+            session_id: Some(self.session_id.clone()),
+            system_prompt: self.system_prompt.clone(),
+            user_id: user_override,
+        };
+        self.last_request = Some((request.clone(), api_client.chat_stream_url(&request)));
 
         let (tx, rx) = std::sync::mpsc::channel::<StreamEvent>();
+        // Set once the reply was stopped early; the stream then drops the connection
+        let abort = Arc::new(AtomicBool::new(false));
+        let stream_abort = abort.clone();
 
         cx.spawn(async move |this, cx| {
-            let tx_clone = tx.clone();
+            // The background task owns the only sender, so the channel reports
+            // Disconnected as soon as the stream ends, terminal event or not
             let stream_result = cx.background_spawn(async move {
-                api_client.chat_stream(&message, Some(session_id), |event| {
-                    let _ = tx_clone.send(event);
+                api_client.chat_stream(&request, |event| {
+                    if stream_abort.load(Ordering::Relaxed) {
+                        return ControlFlow::Break(());
+                    }
+                    let _ = tx.send(event);
+                    ControlFlow::Continue(())
                 })
             });
 
-            // Process streaming events
+            // Process streaming events. After Done/Error the backend may still send
+            // a knowledge update, so keep draining until the stream closes.
+            let mut idle_notified = false;
+            let mut finished = false;
             loop {
-                match rx.try_recv() {
-                    Ok(event) => {
-                        Self::handle_stream_event(&this, cx, event);
-                        // Check if we should break (Done or Error)
-                        if let Ok(should_break) = this.read_with(cx, |app, _| {
-                            !app.is_loading
-                        }) {
-                            if should_break {
-                                break;
+                // Drain everything already queued and render once per wake, so a
+                // fast backend batches chunks instead of building a backlog
+                let mut received = false;
+                let disconnected = loop {
+                    match rx.try_recv() {
+                        Ok(event) => {
+                            if finished && !matches!(event, StreamEvent::KnowledgeUpdate { .. }) {
+                                continue;
+                            }
+                            received = true;
+                            let boundary = matches!(
+                                event,
+                                StreamEvent::Chunk { .. } | StreamEvent::ToolResult { .. }
+                            );
+                            Self::handle_stream_event(&this, cx, event);
+                            if boundary && !finished {
+                                if let Ok(true) =
+                                    this.update(cx, |app, cx| app.stop_at_boundary(cx))
+                                {
+                                    abort.store(true, Ordering::Relaxed);
+                                }
+                            }
+                            if !finished {
+                                finished = matches!(
+                                    this.read_with(cx, |app, _| !app.is_loading),
+                                    Ok(true)
+                                );
                             }
                         }
+                        Err(std::sync::mpsc::TryRecvError::Empty) => break false,
+                        Err(std::sync::mpsc::TryRecvError::Disconnected) => break true,
                     }
-                    Err(std::sync::mpsc::TryRecvError::Empty) => {
-                        smol::Timer::after(std::time::Duration::from_millis(10)).await;
-                    }
-                    Err(std::sync::mpsc::TryRecvError::Disconnected) => {
-                        break;
+                };
+
+                if received {
+                    idle_notified = false;
+                    let _ = this.update(cx, |_, cx| cx.notify());
+                }
+                if disconnected {
+                    break;
+                }
+
+                // Re-render once the stream goes quiet so the typing indicator appears
+                if !idle_notified && !finished {
+                    if let Ok(true) = this.read_with(cx, |app, _| app.stream_is_idle()) {
+                        idle_notified = true;
+                        let _ = this.update(cx, |_, cx| cx.notify());
                     }
                 }
+                smol::Timer::after(std::time::Duration::from_millis(10)).await;
             }
 
             // Wait for background task to finish and handle errors
-            match stream_result.await {
-                Ok(_) => {}
-                Err(e) => {
-                    let _ = this.update(cx, |app, cx| {
-                        if app.is_loading {
-                            app.messages.push(ChatMessage::system(format!(
-                                "⚠️ Error: {}. Is the backend running?",
-                                e
-                            )));
-                            app.is_loading = false;
-                            app.streaming_message_id = None;
-                        }
-                        cx.notify();
-                    });
+            let error = match stream_result.await {
+                Ok(outcome) => {
+                    if !outcome.dropped_events.is_empty() {
+                        let mut kinds = outcome.dropped_events.clone();
+                        kinds.sort();
+                        kinds.dedup();
+                        let _ = this.update(cx, |app, cx| {
+                            app.report_error(format!(
+                                "⚠️ Ignored {} stream event(s) this GUI doesn't understand ({}). \
+                                The backend may be newer than the GUI.",
+                                outcome.dropped_events.len(),
+                                kinds.join(", ")
+                            ));
+                            cx.notify();
+                        });
+                    }
+                    None
                 }
-            }
+                // The backend answered, so don't suggest it is down
+                Err(e) if e.downcast_ref::<RateLimited>().is_some() => Some(e.to_string()),
+                Err(e) => Some(format!("{}. Is the backend running?", e)),
+            };
+
+            // Never leave the input disabled, even if the stream closed without Done/Error
+            let _ = this.update(cx, |app, cx| {
+                app.finish_dangling_stream(&finish_msg_id, error, cx);
+            });
         })
         .detach();
     }
 
+    /// Apply one stream event; the caller notifies once per drained batch
     fn handle_stream_event(
         this: &gpui::WeakEntity<App>,
         cx: &mut AsyncApp,
         event: StreamEvent,
     ) {
         match event {
-            StreamEvent::Context { memories, knowledge } => {
-                let _ = this.update(cx, |app, cx| {
+            StreamEvent::Context {
+                memories,
+                knowledge,
+                knowledge_limit,
+            } => {
+                let _ = this.update(cx, |app, _cx| {
                     // Update sidebar with context used for this query
+                    app.memory_has_more = !memories.is_empty();
+                    app.knowledge_has_more = match knowledge_limit {
+                        Some(limit) => knowledge.len() >= limit,
+                        // Older backends don't say; assume a non-empty list may go on
+                        None => !knowledge.is_empty(),
+                    };
+                    app.context_memory_count = memories.len();
+                    app.context_knowledge_count = knowledge.len();
                     app.current_context_memories = memories;
                     app.current_context_knowledge = knowledge;
-                    cx.notify();
                 });
             }
             StreamEvent::Chunk { content } => {
-                let _ = this.update(cx, |app, cx| {
+                let _ = this.update(cx, |app, _cx| {
+                    app.last_chunk_at = Some(Instant::now());
+                    app.stream_started_at.get_or_insert_with(Instant::now);
                     app.streaming_content.push_str(&content);
                     if let Some(msg) = app.messages.last_mut() {
                         if Some(&msg.id) == app.streaming_message_id.as_ref() {
@@ -118,11 +334,41 @@ impl App {
                             msg.append_text(&content);
                         }
                     }
-                    cx.notify();
+                    app.follow_stream_content();
                 });
             }
-            StreamEvent::ToolStart { name, arguments, id } => {
+            StreamEvent::Thinking { content } => {
+                let _ = this.update(cx, |app, _cx| {
+                    app.last_chunk_at = Some(Instant::now());
+                    if let Some(msg) = app.messages.last_mut() {
+                        if Some(&msg.id) == app.streaming_message_id.as_ref() {
+                            msg.reasoning.push_str(&content);
+                        }
+                    }
+                    app.follow_stream_content();
+                });
+            }
+            StreamEvent::Citation { id, source, title, url } => {
+                let _ = this.update(cx, |app, _cx| {
+                    app.last_chunk_at = Some(Instant::now());
+                    if let Some(msg) = app.messages.last_mut() {
+                        if Some(&msg.id) == app.streaming_message_id.as_ref() {
+                            msg.add_citation(Citation { id, source, title, url });
+                        }
+                    }
+                });
+            }
+            StreamEvent::KnowledgeUpdate { added, updated } => {
+                if added.is_empty() && updated.is_empty() {
+                    return;
+                }
                 let _ = this.update(cx, |app, cx| {
+                    app.show_knowledge_toast(added, updated, cx);
+                });
+            }
+            StreamEvent::ToolStart { name, arguments, id } => {
+                let _ = this.update(cx, |app, _cx| {
+                    app.last_chunk_at = Some(Instant::now());
                     // Add to live_tool_calls for sidebar (backward compat)
                     if !app.live_tool_calls.iter().any(|tc| tc.id == id) {
                         app.live_tool_calls.push(LiveToolCall {
@@ -132,6 +378,9 @@ impl App {
                             result: None,
                             status: ToolStatus::Running,
                             expanded: true,
+                            pinned: false,
+                            started_at: std::time::Instant::now(),
+                            duration_ms: None,
                         });
                         app.tool_expanded = true;
                     }
@@ -146,27 +395,32 @@ impl App {
                                 result: None,
                                 status: ToolStatus::Running,
                                 expanded: true, // Start expanded to show progress
+                                started_at: std::time::Instant::now(),
+                                duration_ms: None,
                             });
                         }
                     }
-                    cx.notify();
+                    app.follow_stream_content();
                 });
             }
             StreamEvent::ToolResult { id, result } => {
                 let _ = this.update(cx, |app, cx| {
+                    app.last_chunk_at = Some(Instant::now());
                     // Update sidebar tool calls
                     if let Some(tc) = app.live_tool_calls.iter_mut().find(|tc| tc.id == id) {
                         tc.result = Some(result.clone());
                         tc.status = ToolStatus::Completed;
+                        tc.duration_ms = Some(tc.started_at.elapsed().as_millis() as u64);
                     }
 
+                    app.load_tool_image(&id, &result, cx);
+
                     // Update inline tool call in message
                     if let Some(msg) = app.messages.last_mut() {
                         if Some(&msg.id) == app.streaming_message_id.as_ref() {
                             msg.update_tool_result(&id, result, ToolStatus::Completed);
                         }
                     }
-                    cx.notify();
                 });
             }
             StreamEvent::Done { tool_calls } => {
@@ -195,22 +449,40 @@ impl App {
                             msg.tool_calls = tcs;
                             // Rebuild content from text segments
                             msg.rebuild_content();
+                            msg.mark_if_empty_response();
                         }
                     }
 
                     app.is_loading = false;
                     app.streaming_message_id = None;
+                    app.last_chunk_at = None;
+                    if !app.window_active {
+                        app.unread_count += 1;
+                        if app.config.notify_on_complete {
+                            play_completion_sound();
+                        }
+                    }
                     app.refresh_sidebar_data(cx);
-                    cx.notify();
+                    app.persist_session(cx);
                 });
             }
-            StreamEvent::Error { content } => {
+            StreamEvent::Error { content, kind } => {
+                let recoverable = StreamEvent::is_recoverable_error(kind.as_deref());
                 let _ = this.update(cx, |app, cx| {
+                    app.log_error(format!("Stream error: {}", content));
                     if let Some(msg) = app.messages.last_mut() {
                         if Some(&msg.id) == app.streaming_message_id.as_ref() {
-                            msg.content = format!("⚠️ Error: {}", content);
-                            msg.role = MessageRole::System;
-                            msg.segments.clear();
+                            msg.fail_running_tools();
+                            if recoverable && !msg.segments.is_empty() {
+                                // Keep what the assistant already said and note the error under it
+                                msg.rebuild_content();
+                                msg.stream_error = Some(content);
+                            } else {
+                                msg.content = format!("⚠️ Error: {}", content);
+                                msg.role = MessageRole::System;
+                                msg.segments.clear();
+                                msg.failed = true;
+                            }
                         }
                     }
                     // Mark all running tools as failed
@@ -221,12 +493,432 @@ impl App {
                     }
                     app.is_loading = false;
                     app.streaming_message_id = None;
-                    cx.notify();
+                    app.last_chunk_at = None;
+                    app.persist_session(cx);
                 });
             }
         }
     }
 
+    /// Reset streaming state left behind by a stream that failed or ended without
+    /// a Done/Error event, so the input never stays disabled
+    pub fn finish_dangling_stream(
+        &mut self,
+        stream_msg_id: &str,
+        error: Option<String>,
+        cx: &mut Context<Self>,
+    ) {
+        // Done/Error already cleaned up, or a newer stream owns the state now
+        if self.streaming_message_id.as_deref() != Some(stream_msg_id) {
+            return;
+        }
+
+        let error = error.unwrap_or_else(|| "the stream closed without a response".to_string());
+        self.log_error(format!("Stream dropped: {}", error));
+        if let Some(stream_id) = self.streaming_message_id.take() {
+            if let Some(msg) = self.messages.iter_mut().find(|m| m.id == stream_id) {
+                msg.finish_dropped_stream(error);
+            }
+        }
+        for tc in &mut self.live_tool_calls {
+            if tc.status == ToolStatus::Running {
+                tc.status = ToolStatus::Failed;
+            }
+        }
+        self.is_loading = false;
+        self.last_chunk_at = None;
+        self.persist_session(cx);
+        cx.notify();
+    }
+
+    /// End the reply here if "Finish & stop" was requested and no tool is still
+    /// running; true if the stream was stopped
+    pub fn stop_at_boundary(&mut self, cx: &mut Context<Self>) -> bool {
+        if !self.stop_after_step
+            || self
+                .live_tool_calls
+                .iter()
+                .any(|tc| tc.status == ToolStatus::Running)
+        {
+            return false;
+        }
+        let Some(stream_id) = self.streaming_message_id.take() else {
+            return false;
+        };
+
+        if let Some(msg) = self.messages.iter_mut().find(|m| m.id == stream_id) {
+            msg.rebuild_content();
+            msg.mark_if_empty_response();
+        }
+        self.messages.push(ChatMessage::system(
+            "Stopped after the current step; the partial answer is kept.",
+        ));
+        self.stop_after_step = false;
+        self.is_loading = false;
+        self.last_chunk_at = None;
+        self.refresh_sidebar_data(cx);
+        self.persist_session(cx);
+        cx.notify();
+        true
+    }
+
+    /// Whether the stream has been quiet long enough to show the typing indicator
+    pub fn stream_is_idle(&self) -> bool {
+        self.is_loading
+            && self
+                .last_chunk_at
+                .is_some_and(|at| at.elapsed() >= Duration::from_millis(500))
+    }
+
+    /// Words so far and characters per second of the reply being streamed
+    pub fn stream_rate(&self) -> Option<(usize, f32)> {
+        let started = self.stream_started_at?;
+        if !self.is_loading || self.streaming_content.is_empty() {
+            return None;
+        }
+        let secs = started.elapsed().as_secs_f32();
+        let chars = self.streaming_content.chars().count();
+        let words = self.streaming_content.split_whitespace().count();
+        Some((words, if secs > 0.1 { chars as f32 / secs } else { 0. }))
+    }
+
+    /// Resubmit the user prompt that led to a failed reply
+    pub fn retry_failed_message(&mut self, msg_id: &str, cx: &mut Context<Self>) {
+        if self.is_loading {
+            return;
+        }
+        let Some(idx) = self.messages.iter().position(|m| m.id == msg_id && m.failed) else {
+            return;
+        };
+        let Some(prompt) = self.messages[..idx]
+            .iter()
+            .rev()
+            .find(|m| m.role == MessageRole::User)
+            .map(|m| m.content.clone())
+        else {
+            return;
+        };
+
+        // The error message is replaced by the new attempt
+        self.messages.remove(idx);
+        self.send_message_with_text(prompt, cx);
+    }
+
+    /// Send the last user message again as a new message. Unlike retrying a
+    /// failed reply, this appends instead of replacing anything.
+    pub fn resend_last_user_message(&mut self, cx: &mut Context<Self>) {
+        let Some(text) = self
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.role == MessageRole::User)
+            .map(|m| m.content.clone())
+        else {
+            return;
+        };
+        self.send_message_with_text(text, cx);
+    }
+
+    /// Retry a failed tool call and update it wherever it is shown
+    pub fn retry_tool(&mut self, tool_id: String, cx: &mut Context<Self>) {
+        if self.retrying_tools.contains(&tool_id) || self.unretryable_tools.contains_key(&tool_id) {
+            return;
+        }
+        self.retrying_tools.insert(tool_id.clone());
+        if let Some(tc) = self.live_tool_calls.iter_mut().find(|tc| tc.id == tool_id) {
+            tc.status = ToolStatus::Running;
+            tc.started_at = std::time::Instant::now();
+            tc.duration_ms = None;
+        }
+        for msg in &mut self.messages {
+            msg.restart_tool(&tool_id);
+        }
+        cx.notify();
+
+        let api_client = self.api_client.clone();
+        let session_id = self.session_id.clone();
+        cx.spawn(async move |this, cx| {
+            let id = tool_id.clone();
+            let result = cx
+                .background_spawn(async move { api_client.retry_tool(&session_id, &id) })
+                .await;
+
+            let _ = this.update(cx, |app, cx| {
+                app.retrying_tools.remove(&tool_id);
+                let (result_text, status) = match result {
+                    Ok(text) => {
+                        app.load_tool_image(&tool_id, &text, cx);
+                        (text, ToolStatus::Completed)
+                    }
+                    Err(e) => {
+                        // Don't offer the retry again for a tool the backend refused
+                        app.unretryable_tools
+                            .insert(tool_id.clone(), format!("Retry unavailable: {}", e));
+                        app.log_error(format!("Tool retry failed: {}", e));
+                        (format!("Retry failed: {}", e), ToolStatus::Failed)
+                    }
+                };
+                if let Some(tc) = app.live_tool_calls.iter_mut().find(|tc| tc.id == tool_id) {
+                    tc.result = Some(result_text.clone());
+                    tc.status = status.clone();
+                    tc.duration_ms = Some(tc.started_at.elapsed().as_millis() as u64);
+                }
+                for msg in &mut app.messages {
+                    msg.update_tool_result(&tool_id, result_text.clone(), status.clone());
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Write a tool call's full (untruncated) result to a file the user picks
+    pub fn save_tool_result(&mut self, tool_id: String, cx: &mut Context<Self>) {
+        let live = self
+            .live_tool_calls
+            .iter()
+            .find(|tc| tc.id == tool_id)
+            .and_then(|tc| Some((tc.name.clone(), tc.result.clone()?)));
+        let found = live.or_else(|| {
+            self.messages.iter().flat_map(|msg| &msg.segments).find_map(|seg| match seg {
+                MessageSegment::ToolCall(tc) if tc.id == tool_id => {
+                    Some((tc.name.clone(), tc.result.clone()?))
+                }
+                _ => None,
+            })
+        });
+        let Some((tool_name, result)) = found else {
+            self.show_transient_message("No result to save for this tool call.".to_string(), cx);
+            return;
+        };
+
+        let directory = dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| std::path::PathBuf::from("."));
+        let file_name = format!("{}-result.txt", tool_name);
+        let path_prompt = cx.prompt_for_new_path(&directory, Some(&file_name));
+
+        cx.spawn(async move |this, cx| {
+            // Cancelled dialogs and platform errors both leave nothing to do
+            let Ok(Ok(Some(path))) = path_prompt.await else {
+                return;
+            };
+            let target = path.clone();
+            let written = cx
+                .background_spawn(async move { std::fs::write(&target, result) })
+                .await;
+
+            let _ = this.update(cx, |app, cx| {
+                let text = match written {
+                    Ok(()) => format!("✓ Saved {} result to {}", tool_name, path.display()),
+                    Err(e) => {
+                        let text = format!("⚠️ Could not save result to {}: {}", path.display(), e);
+                        app.log_error(text.clone());
+                        text
+                    }
+                };
+                app.show_transient_message(text, cx);
+            });
+        })
+        .detach();
+    }
+
+    /// If a tool result is an image, decode or download it for inline display
+    pub fn load_tool_image(&mut self, tool_id: &str, result: &str, cx: &mut Context<Self>) {
+        let Some(reference) = detect_image_reference(result) else {
+            return;
+        };
+        self.tool_images.insert(tool_id.to_string(), ToolImage::Loading);
+
+        let api_client = self.api_client.clone();
+        let tool_id = tool_id.to_string();
+        cx.spawn(async move |this, cx| {
+            let source = reference.clone();
+            let image = cx
+                .background_spawn(async move {
+                    let (mime, bytes) = match &source {
+                        ImageReference::DataUri { mime, base64 } => (
+                            mime.clone(),
+                            base64::engine::general_purpose::STANDARD.decode(base64)?,
+                        ),
+                        ImageReference::Url(url) => api_client.fetch_image(url)?,
+                    };
+                    let format = image_format(&mime)
+                        .or_else(|| image_format(&source.extension()))
+                        .ok_or_else(|| anyhow::anyhow!("unsupported image type {}", mime))?;
+                    anyhow::Ok(Arc::new(Image::from_bytes(format, bytes)))
+                })
+                .await;
+
+            let _ = this.update(cx, |app, cx| {
+                let state = match image {
+                    Ok(image) => ToolImage::Ready { image, reference },
+                    Err(_) => ToolImage::Failed,
+                };
+                app.tool_images.insert(tool_id, state);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Re-fetch the memory panel with a larger limit for the last query
+    pub fn load_more_memories(&mut self, cx: &mut Context<Self>) {
+        if self.loading_more || !self.memory_has_more {
+            return;
+        }
+        self.loading_more = true;
+        cx.notify();
+
+        let api_client = self.api_client.clone();
+        let query = self.last_query.clone();
+        let user_id = self.current_user_id.clone();
+        let limit = self.current_context_memories.len() + SIDEBAR_PAGE_SIZE;
+        cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_spawn(async move { api_client.get_memory_context(&query, limit, &user_id) })
+                .await;
+
+            let _ = this.update(cx, |app, cx| {
+                app.loading_more = false;
+                match result {
+                    Ok(response) => {
+                        app.memory_has_more = response.items.len() >= limit;
+                        app.current_context_memories = response.items;
+                    }
+                    Err(e) => {
+                        app.memory_has_more = false;
+                        let text = format!("⚠️ Could not load more memories: {}", e);
+                        app.log_error(text.clone());
+                        app.show_transient_message(text, cx);
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Re-fetch the knowledge panel with a larger limit for the last query
+    pub fn load_more_knowledge(&mut self, cx: &mut Context<Self>) {
+        if self.loading_more || !self.knowledge_has_more {
+            return;
+        }
+        self.loading_more = true;
+        cx.notify();
+
+        let api_client = self.api_client.clone();
+        let query = self.last_query.clone();
+        let limit = self.current_context_knowledge.len() + SIDEBAR_PAGE_SIZE;
+        cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_spawn(async move { api_client.search_knowledge(&query, limit) })
+                .await;
+
+            let _ = this.update(cx, |app, cx| {
+                app.loading_more = false;
+                match result {
+                    Ok(response) => {
+                        app.knowledge_has_more = response.triples.len() >= limit;
+                        app.current_context_knowledge = response.triples;
+                    }
+                    Err(e) => {
+                        app.knowledge_has_more = false;
+                        let text = format!("⚠️ Could not load more knowledge: {}", e);
+                        app.log_error(text.clone());
+                        app.show_transient_message(text, cx);
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Record a client-side error for /errors, dropping the oldest past the cap
+    pub fn log_error(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        let message = message.trim_start_matches("⚠️").trim().to_string();
+        self.error_log
+            .push((chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(), message));
+        if self.error_log.len() > ERROR_LOG_LIMIT {
+            self.error_log.remove(0);
+        }
+    }
+
+    /// Show an error in the chat and record it in the error log
+    pub fn report_error(&mut self, message: String) {
+        self.log_error(message.clone());
+        self.messages.push(ChatMessage::system(message));
+    }
+
+    /// Show a system message that removes itself after a few seconds
+    pub fn show_transient_message(&mut self, text: String, cx: &mut Context<Self>) {
+        let message = ChatMessage::system(text);
+        let message_id = message.id.clone();
+        self.messages.push(message);
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            smol::Timer::after(Duration::from_secs(5)).await;
+            let _ = this.update(cx, |app, cx| {
+                app.messages.retain(|m| m.id != message_id);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Show what the last reply added to or changed in the knowledge base
+    pub fn show_knowledge_toast(
+        &mut self,
+        added: Vec<KnowledgeChange>,
+        updated: Vec<KnowledgeChange>,
+        cx: &mut Context<Self>,
+    ) {
+        let toast_id = uuid::Uuid::new_v4().to_string();
+        self.knowledge_toast = Some(KnowledgeToast {
+            id: toast_id.clone(),
+            added,
+            updated,
+        });
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            smol::Timer::after(Duration::from_secs(8)).await;
+            let _ = this.update(cx, |app, cx| {
+                if app.knowledge_toast.as_ref().is_some_and(|t| t.id == toast_id) {
+                    app.knowledge_toast = None;
+                    cx.notify();
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// Rough token count of the conversation (chars / 4), for the context warning
+    pub fn estimated_context_tokens(&self) -> usize {
+        self.messages
+            .iter()
+            .filter(|msg| msg.role != MessageRole::System)
+            // Chars, not bytes: non-ASCII text would otherwise count 2-3x
+            .map(|msg| msg.content.chars().count())
+            .sum::<usize>()
+            / 4
+    }
+
+    /// Save the current conversation to disk in the background
+    pub fn persist_session(&self, cx: &mut Context<Self>) {
+        let user_id = self.current_user_id.clone();
+        let session_id = self.session_id.clone();
+        let meta = self.session_meta.clone();
+        let messages = self.messages[self.session_start()..].to_vec();
+        cx.background_spawn(async move {
+            let _ = sessions::save_session(&user_id, &session_id, &meta, &messages);
+        })
+        .detach();
+    }
+
     /// Handle slash commands
     pub fn handle_command(&mut self, command: Command, cx: &mut Context<Self>) {
         match command {
@@ -234,43 +926,114 @@ impl App {
                 self.messages.push(ChatMessage::system(
                     "**Available Commands:**\n\n\
                     /stats - Show agent statistics\n\
-                    /memory - Show memory statistics\n\
-                    /knowledge - Show learned knowledge\n\
+                    /memory [raw] - Show memory statistics (raw = JSON)\n\
+                    /knowledge [raw] - Show learned knowledge (raw = JSON)\n\
                     /clear - Clear conversation\n\
                     /reconnect - Reconnect to backend\n\
-                    /config <key> <value> - Update config\n\n\
-                    Config keys: show_memory, show_knowledge, show_tools",
+                    /config [key] [value] - Show config values, or update one\n\
+                    /model <name> - Switch the LLM model\n\
+                    /provider [name] - List providers or switch to one\n\
+                    /history - List saved sessions\n\
+                    /load <session_id> - Restore a saved session\n\
+                    /filter system on|off - Hide or show system messages\n\
+                    /system <prompt> - Set this session's system prompt (empty clears it)\n\
+                    /whoami - Show user, session and connection details\n\
+                    /rename <name> - Rename the current user\n\
+                    /lastrequest - Show the last chat request as sent\n\
+                    /errors - Show recent client-side errors\n\
+                    /export json <path> - Export the conversation as JSON\n\
+                    /profile [name] - List backend profiles or switch to one\n\
+                    /profile add <name> <url> - Save a backend profile\n\
+                    /profile remove <name> - Delete a backend profile\n\n\
+                    Config keys: show_memory, show_knowledge, show_tools, render_markdown, density, timestamp_format, clock_12h, tool_default, compact_during_stream, ascii_mode, detect_tool_errors, reduce_motion, message_width, panel_max_height, tool_panel_max_height, clear_on_user_switch, notify_on_complete, max_input_chars, stats_interval, context_warn_threshold, debug, accent_<red|orange|yellow|green|cyan|purple>",
                 ));
             }
             Command::Stats => self.handle_stats_command(cx),
-            Command::Memory => self.handle_memory_command(cx),
-            Command::Knowledge => self.handle_knowledge_command(cx),
+            Command::Memory { raw } => self.handle_memory_command(raw, cx),
+            Command::Knowledge { raw } => self.handle_knowledge_command(raw, cx),
             Command::Clear => self.handle_clear_command(cx),
             Command::Reconnect => {
+                self.cancel_reconnect();
                 self.messages.clear();
                 self.messages.push(ChatMessage::system("Reconnecting..."));
                 self.connection_status = ConnectionStatus::Connecting;
                 cx.notify();
                 self.initialize(cx);
             }
-            Command::Config { key, value } => {
-                let response = match key.as_str() {
-                    "show_memory" => {
-                        self.config.show_memory = value.to_lowercase() == "true";
-                        format!("✓ show_memory set to {}", self.config.show_memory)
-                    }
-                    "show_knowledge" => {
-                        self.config.show_knowledge = value.to_lowercase() == "true";
-                        format!("✓ show_knowledge set to {}", self.config.show_knowledge)
-                    }
-                    "show_tools" => {
-                        self.config.show_tool_use = value.to_lowercase() == "true";
-                        format!("✓ show_tools set to {}", self.config.show_tool_use)
+            Command::Config { key: None, .. } => {
+                let lines: Vec<String> = CONFIG_KEYS
+                    .iter()
+                    .chain(ACCENT_KEYS.iter())
+                    .filter_map(|key| {
+                        self.config_value(key).map(|value| format!("{} = {}", key, value))
+                    })
+                    .collect();
+                self.messages.push(ChatMessage::system(format!(
+                    "**Config:**\n{}\n\nChange one with /config <key> <value>",
+                    lines.join("\n")
+                )));
+            }
+            Command::Config {
+                key: Some(key),
+                value: None,
+            } => {
+                self.messages.push(ChatMessage::system(match self.config_value(&key) {
+                    Some(value) => format!("{} = {}", key, value),
+                    None => format!("Unknown config key: {}", key),
+                }));
+            }
+            Command::Config {
+                key: Some(key),
+                value: Some(value),
+            } => {
+                let response = match self.apply_config(&key, &value, cx) {
+                    Ok(response) => {
+                        self.config.mark_explicit(&key);
+                        self.save_config();
+                        response
                     }
-                    _ => format!("Unknown config key: {}", key),
+                    Err(error) => error,
                 };
                 self.messages.push(ChatMessage::system(response));
             }
+            Command::Model { name } => self.handle_model_command(name, cx),
+            Command::Provider { name: None } => self.handle_providers_list_command(cx),
+            Command::Provider { name: Some(name) } => self.handle_provider_command(name, cx),
+            Command::History => self.handle_history_command(),
+            Command::Load { session_id } => self.handle_load_command(session_id),
+            Command::Filter { role, hidden } => {
+                if role == "system" {
+                    self.hide_system_messages = hidden;
+                    if !hidden {
+                        self.messages.push(ChatMessage::system("✓ System messages shown"));
+                    }
+                } else {
+                    self.messages.push(ChatMessage::system(format!(
+                        "Unknown filter: {}. Only `system` can be filtered.",
+                        role
+                    )));
+                }
+            }
+            Command::System { prompt } => {
+                if prompt.is_empty() {
+                    self.system_prompt = None;
+                    self.messages.push(ChatMessage::system("✓ System prompt cleared"));
+                } else {
+                    self.system_prompt = Some(prompt);
+                    self.messages.push(ChatMessage::system(
+                        "✓ System prompt set for this session",
+                    ));
+                }
+            }
+            Command::WhoAmI => self.handle_whoami_command(),
+            Command::Rename { new_name } => self.handle_rename_command(new_name, cx),
+            Command::LastRequest => self.handle_last_request_command(),
+            Command::Errors => self.show_error_log = true,
+            Command::Export { format, path } => self.handle_export_command(&format, &path),
+            Command::Profiles => self.handle_profiles_command(),
+            Command::Profile { name } => self.switch_profile(&name, cx),
+            Command::ProfileAdd { name, url } => self.handle_profile_add_command(name, url),
+            Command::ProfileRemove { name } => self.handle_profile_remove_command(&name),
             Command::Unknown(cmd) => {
                 self.messages.push(ChatMessage::system(format!(
                     "Unknown command: /{}. Type /help for help.",
@@ -281,6 +1044,635 @@ impl App {
         cx.notify();
     }
 
+    /// Current value of a `/config` key as it would be typed, or None if unknown
+    fn config_value(&self, key: &str) -> Option<String> {
+        let config = &self.config;
+        let value = match key {
+            "show_memory" => config.show_memory.to_string(),
+            "show_knowledge" => config.show_knowledge.to_string(),
+            "show_tools" => config.show_tool_use.to_string(),
+            "render_markdown" => config.render_markdown.to_string(),
+            "density" => config.density.as_str().to_string(),
+            "timestamp_format" => config.timestamp_format.as_str().to_string(),
+            "clock_12h" => config.clock_12h.to_string(),
+            "tool_default" => config.tool_display.as_str().to_string(),
+            "compact_during_stream" => config.compact_during_stream.to_string(),
+            "ascii_mode" => config.ascii_mode.to_string(),
+            "detect_tool_errors" => config.detect_tool_errors.to_string(),
+            "reduce_motion" => config.reduce_motion.to_string(),
+            "message_width" => config.max_message_width.to_string(),
+            "panel_max_height" => config.panel_max_height.to_string(),
+            "tool_panel_max_height" => config.tool_panel_max_height.to_string(),
+            "clear_on_user_switch" => config.clear_on_user_switch.to_string(),
+            "notify_on_complete" => config.notify_on_complete.to_string(),
+            "max_input_chars" => config.max_input_chars.to_string(),
+            "stats_interval" => config.stats_interval_secs.to_string(),
+            "context_warn_threshold" => config.context_warn_threshold.to_string(),
+            "debug" => config.debug.to_string(),
+            key if ACCENT_KEYS.contains(&key) => config
+                .accent_colors
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| "default".to_string()),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Apply a `/config <key> <value>` pair, returning the confirmation text
+    fn apply_config(
+        &mut self,
+        key: &str,
+        value: &str,
+        cx: &mut Context<Self>,
+    ) -> Result<String, String> {
+        match key {
+            "show_memory" => {
+                self.config.show_memory = value.to_lowercase() == "true";
+                Ok(format!("✓ show_memory set to {}", self.config.show_memory))
+            }
+            "show_knowledge" => {
+                self.config.show_knowledge = value.to_lowercase() == "true";
+                Ok(format!("✓ show_knowledge set to {}", self.config.show_knowledge))
+            }
+            "show_tools" => {
+                self.config.show_tool_use = value.to_lowercase() == "true";
+                Ok(format!("✓ show_tools set to {}", self.config.show_tool_use))
+            }
+            "render_markdown" => {
+                self.config.render_markdown = value.to_lowercase() == "true";
+                Ok(format!("✓ render_markdown set to {}", self.config.render_markdown))
+            }
+            "density" => {
+                self.config.density = Density::parse(value).ok_or_else(|| {
+                    format!("Invalid density: {} (comfortable or compact)", value)
+                })?;
+                Ok(format!("✓ density set to {}", self.config.density.as_str()))
+            }
+            "timestamp_format" => {
+                self.config.timestamp_format = TimestampFormat::parse(value).ok_or_else(|| {
+                    format!("Invalid timestamp_format: {} (time, date or relative)", value)
+                })?;
+                Ok(format!(
+                    "✓ timestamp_format set to {}",
+                    self.config.timestamp_format.as_str()
+                ))
+            }
+            "tool_default" => {
+                self.config.tool_display = ToolDisplayMode::parse(value).ok_or_else(|| {
+                    format!("Invalid tool_default: {} (args, result or both)", value)
+                })?;
+                Ok(format!(
+                    "✓ Expanded tool calls show {}",
+                    self.config.tool_display.as_str()
+                ))
+            }
+            "ascii_mode" => {
+                self.config.ascii_mode = value.to_lowercase() == "true";
+                Ok(format!("✓ ascii_mode set to {}", self.config.ascii_mode))
+            }
+            "reduce_motion" => {
+                self.config.reduce_motion = value.to_lowercase() == "true";
+                Ok(format!("✓ reduce_motion set to {}", self.config.reduce_motion))
+            }
+            "detect_tool_errors" => {
+                self.config.detect_tool_errors = value.to_lowercase() == "true";
+                Ok(format!("✓ detect_tool_errors set to {}", self.config.detect_tool_errors))
+            }
+            "compact_during_stream" => {
+                self.config.compact_during_stream = value.to_lowercase() == "true";
+                Ok(format!(
+                    "✓ compact_during_stream set to {}",
+                    self.config.compact_during_stream
+                ))
+            }
+            "clock_12h" => {
+                self.config.clock_12h = value.to_lowercase() == "true";
+                Ok(format!("✓ clock_12h set to {}", self.config.clock_12h))
+            }
+            "clear_on_user_switch" => {
+                self.config.clear_on_user_switch = value.to_lowercase() == "true";
+                Ok(format!(
+                    "✓ clear_on_user_switch set to {}",
+                    self.config.clear_on_user_switch
+                ))
+            }
+            "notify_on_complete" => {
+                self.config.notify_on_complete = value.to_lowercase() == "true";
+                Ok(format!("✓ notify_on_complete set to {}", self.config.notify_on_complete))
+            }
+            "message_width" => {
+                let width = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|w| (MIN_MESSAGE_WIDTH..=MAX_MESSAGE_WIDTH).contains(w))
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid message_width: {} (pixels, {}-{})",
+                            value, MIN_MESSAGE_WIDTH, MAX_MESSAGE_WIDTH
+                        )
+                    })?;
+                self.config.max_message_width = width;
+                Ok(format!("✓ message_width set to {}px", width))
+            }
+            "panel_max_height" | "tool_panel_max_height" => {
+                let height = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|h| (MIN_PANEL_HEIGHT..=MAX_PANEL_HEIGHT).contains(h))
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid {}: {} (pixels, {}-{})",
+                            key, value, MIN_PANEL_HEIGHT, MAX_PANEL_HEIGHT
+                        )
+                    })?;
+                if key == "panel_max_height" {
+                    self.config.panel_max_height = height;
+                } else {
+                    self.config.tool_panel_max_height = height;
+                }
+                Ok(format!("✓ {} set to {}px", key, height))
+            }
+            "max_input_chars" => {
+                let chars = value.parse::<usize>().map_err(|_| {
+                    format!("Invalid max_input_chars: {} (characters, 0 = off)", value)
+                })?;
+                self.config.max_input_chars = chars;
+                Ok(if chars == 0 {
+                    "✓ Input length limit disabled".to_string()
+                } else {
+                    format!("✓ Messages are limited to {} characters", chars)
+                })
+            }
+            "context_warn_threshold" => {
+                let tokens = value.parse::<usize>().map_err(|_| {
+                    format!("Invalid context_warn_threshold: {} (tokens, 0 = off)", value)
+                })?;
+                self.config.context_warn_threshold = tokens;
+                Ok(if tokens == 0 {
+                    "✓ Context size warning disabled".to_string()
+                } else {
+                    format!("✓ Warn when the conversation passes ~{} tokens", tokens)
+                })
+            }
+            "debug" => {
+                self.config.debug = value.to_lowercase() == "true";
+                debug_log::init(self.config.debug);
+                Ok(match (debug_log::is_enabled(), debug_log::log_path()) {
+                    (true, Some(path)) => format!("✓ Debug log enabled: {}", path.display()),
+                    (true, None) => "✓ Debug log enabled (no config directory to write to)".to_string(),
+                    (false, _) => "✓ Debug log disabled".to_string(),
+                })
+            }
+            "stats_interval" => {
+                let secs = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid stats_interval: {} (seconds, 0 = off)", value))?;
+                self.config.stats_interval_secs = secs;
+                self.restart_stats_polling(cx);
+                Ok(if secs == 0 {
+                    "✓ Live stats disabled".to_string()
+                } else {
+                    format!("✓ Live stats refresh every {}s", secs)
+                })
+            }
+            key if ACCENT_KEYS.contains(&key) => {
+                if value.eq_ignore_ascii_case("default") {
+                    self.theme.set_accent(key, None);
+                    apply_scrollbar_colors(&self.theme, cx);
+                    self.config.accent_colors.remove(key);
+                    return Ok(format!("✓ {} reset to default", key));
+                }
+                let color = parse_hex_color(value).ok_or_else(|| {
+                    format!("Invalid color: {} (hex like #78dce8, or default)", value)
+                })?;
+                let hex = format!("#{}", value.trim_start_matches('#').to_lowercase());
+                self.theme.set_accent(key, Some(color));
+                apply_scrollbar_colors(&self.theme, cx);
+                self.config.accent_colors.insert(key.to_string(), hex.clone());
+                Ok(format!("✓ {} set to {}", key, hex))
+            }
+            _ => Err(format!("Unknown config key: {}", key)),
+        }
+    }
+
+    fn handle_profiles_command(&mut self) {
+        if self.config.profiles.is_empty() {
+            self.messages.push(ChatMessage::system(
+                "No profiles saved. Add one with /profile add <name> <url>",
+            ));
+            return;
+        }
+
+        let active = self.config.active_profile.as_deref();
+        let list = self
+            .config
+            .profiles
+            .iter()
+            .map(|p| {
+                let marker = if Some(p.name.as_str()) == active { " ✓" } else { "" };
+                format!("- **{}** ({}){}", p.name, p.url, marker)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.messages.push(ChatMessage::system(format!(
+            "**Profiles:**\n{}\n\nSwitch with /profile <name>",
+            list
+        )));
+    }
+
+    fn handle_profile_add_command(&mut self, name: String, url: String) {
+        if name.is_empty() || url.is_empty() {
+            self.messages
+                .push(ChatMessage::system("Usage: /profile add <name> <url>"));
+            return;
+        }
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            self.messages.push(ChatMessage::system(format!(
+                "Invalid backend URL: {}. It must start with http:// or https://",
+                url
+            )));
+            return;
+        }
+
+        let message = match self.config.profiles.iter_mut().find(|p| p.name == name) {
+            Some(profile) => {
+                profile.url = url.clone();
+                format!("✓ Profile {} updated to {}", name, url)
+            }
+            None => {
+                self.config.profiles.push(Profile {
+                    name: name.clone(),
+                    url: url.clone(),
+                });
+                format!("✓ Profile {} added ({})", name, url)
+            }
+        };
+        self.save_config();
+        self.messages.push(ChatMessage::system(message));
+    }
+
+    fn handle_profile_remove_command(&mut self, name: &str) {
+        if name.is_empty() {
+            self.messages
+                .push(ChatMessage::system("Usage: /profile remove <name>"));
+            return;
+        }
+        let before = self.config.profiles.len();
+        self.config.profiles.retain(|p| p.name != name);
+        if self.config.profiles.len() == before {
+            self.messages
+                .push(ChatMessage::system(format!("Unknown profile: {}", name)));
+            return;
+        }
+
+        if self.config.active_profile.as_deref() == Some(name) {
+            self.config.active_profile = None;
+        }
+        self.save_config();
+        self.messages
+            .push(ChatMessage::system(format!("✓ Profile {} removed", name)));
+    }
+
+    fn handle_export_command(&mut self, format: &str, path: &str) {
+        if format != "json" || path.is_empty() {
+            self.messages
+                .push(ChatMessage::system("Usage: /export json <path>"));
+            return;
+        }
+
+        let path = export::expand_home(path);
+        let json = self.export_conversation_json();
+        let result = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => std::fs::create_dir_all(dir),
+            _ => Ok(()),
+        }
+        .and_then(|_| std::fs::write(&path, json));
+
+        match result {
+            Ok(()) => self.messages.push(ChatMessage::system(format!(
+                "✓ Conversation exported to {}",
+                path.display()
+            ))),
+            Err(e) => {
+                self.report_error(format!("⚠️ Could not export to {}: {}", path.display(), e))
+            }
+        }
+    }
+
+    /// Persist the current config, reporting failures in the chat
+    pub fn save_config(&mut self) {
+        if let Err(e) = self.config.save() {
+            self.report_error(format!("⚠️ Could not save config: {}", e));
+        }
+    }
+
+    fn handle_model_command(&mut self, name: String, cx: &mut Context<Self>) {
+        let api_client = self.api_client.clone();
+        self.messages
+            .push(ChatMessage::system(format!("Switching model to {}...", name)));
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let model = name.clone();
+            let result = cx
+                .background_spawn(async move { api_client.set_model(&model) })
+                .await;
+
+            let _ = this.update(cx, |app, cx| {
+                app.messages.pop();
+                match result {
+                    Ok(info) => {
+                        app.agent_model = info.model;
+                        app.agent_provider = info.provider;
+                        app.agent_skills = info.skills;
+                        app.agent_tools = info.tools;
+                        app.messages.push(ChatMessage::system(format!(
+                            "✓ Model switched to {} ({})",
+                            app.agent_model, app.agent_provider
+                        )));
+                    }
+                    Err(e) => {
+                        app.report_error(format!(
+                            "⚠️ Could not switch model to {}: {}",
+                            name, e
+                        ));
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn handle_rename_command(&mut self, new_name: String, cx: &mut Context<Self>) {
+        if new_name.is_empty() {
+            self.messages.push(ChatMessage::system("Usage: /rename <name>"));
+            return;
+        }
+        let old_name = self.current_user_id.clone();
+        if new_name == old_name || self.available_users.contains(&new_name) {
+            self.messages.push(ChatMessage::system(format!(
+                "⚠️ User {} already exists",
+                new_name
+            )));
+            return;
+        }
+
+        let api_client = self.api_client.clone();
+        self.messages.push(ChatMessage::system(format!(
+            "Renaming {} to {}...",
+            old_name, new_name
+        )));
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let (old, new) = (old_name.clone(), new_name.clone());
+            let result = cx
+                .background_spawn(async move { api_client.rename_user(&old, &new) })
+                .await;
+
+            let _ = this.update(cx, |app, cx| {
+                app.messages.pop();
+                match result {
+                    Ok(response) if response.success => {
+                        if app.current_user_id == old_name {
+                            app.current_user_id = response.user_id.clone();
+                        }
+                        match app.available_users.iter_mut().find(|u| **u == old_name) {
+                            Some(entry) => *entry = response.user_id.clone(),
+                            None => app.available_users.push(response.user_id.clone()),
+                        }
+                        app.messages.push(ChatMessage::system(format!(
+                            "✓ Renamed {} to {}",
+                            old_name, response.user_id
+                        )));
+                        match sessions::move_user_sessions(&old_name, &response.user_id) {
+                            Ok(0) => {}
+                            Ok(count) => app.messages.push(ChatMessage::system(format!(
+                                "{} saved session(s) already existed under {} and were left under {}",
+                                count, response.user_id, old_name
+                            ))),
+                            Err(e) => app.report_error(format!(
+                                "⚠️ Some saved sessions were left under {}: {}",
+                                old_name, e
+                            )),
+                        }
+                        app.restart_stats_polling(cx);
+                    }
+                    Ok(response) => {
+                        app.report_error(format!(
+                            "⚠️ Could not rename {}: {}",
+                            old_name,
+                            response.error.unwrap_or_else(|| "rejected by backend".to_string())
+                        ));
+                    }
+                    Err(e) => {
+                        app.report_error(format!(
+                            "⚠️ Could not rename {}: {}",
+                            old_name, e
+                        ));
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn handle_providers_list_command(&mut self, cx: &mut Context<Self>) {
+        let api_client = self.api_client.clone();
+        self.messages.push(ChatMessage::system("Fetching providers..."));
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_spawn(async move { api_client.get_providers() })
+                .await;
+
+            let _ = this.update(cx, |app, cx| {
+                app.messages.pop();
+                let text = match result {
+                    Ok(providers) if providers.is_empty() => {
+                        "No providers reported by the backend.".to_string()
+                    }
+                    Ok(providers) => {
+                        let mut text = String::from("🔌 **Providers**\n\n");
+                        for provider in providers {
+                            let marker = if provider == app.agent_provider { " (current)" } else { "" };
+                            text.push_str(&format!("• {}{}\n", provider, marker));
+                        }
+                        text.push_str("\nUse /provider <name> to switch.");
+                        text
+                    }
+                    Err(e) => {
+                        let text = format!("⚠️ Could not list providers: {}", e);
+                        app.log_error(text.clone());
+                        text
+                    }
+                };
+                app.messages.push(ChatMessage::system(text));
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn handle_provider_command(&mut self, name: String, cx: &mut Context<Self>) {
+        let api_client = self.api_client.clone();
+        let previous_model = self.agent_model.clone();
+        self.messages
+            .push(ChatMessage::system(format!("Switching provider to {}...", name)));
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let provider = name.clone();
+            let result = cx
+                .background_spawn(async move { api_client.set_provider(&provider) })
+                .await;
+
+            let _ = this.update(cx, |app, cx| {
+                app.messages.pop();
+                match result {
+                    Ok(info) => {
+                        app.agent_model = info.model;
+                        app.agent_provider = info.provider;
+                        app.agent_skills = info.skills;
+                        app.agent_tools = info.tools;
+                        let mut text = format!("✓ Provider switched to {}", app.agent_provider);
+                        // Providers have their own model lists, so the backend may pick a new one
+                        if app.agent_model != previous_model {
+                            text.push_str(&format!(
+                                "\nModel reset to {} (was {}). Use /model <name> to change it.",
+                                app.agent_model, previous_model
+                            ));
+                        }
+                        app.messages.push(ChatMessage::system(text));
+                    }
+                    Err(e) => {
+                        app.report_error(format!(
+                            "⚠️ Could not switch provider to {}: {}",
+                            name, e
+                        ));
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn handle_whoami_command(&mut self) {
+        let status = match &self.connection_status {
+            ConnectionStatus::Connected => "Connected".to_string(),
+            ConnectionStatus::Connecting => "Connecting".to_string(),
+            ConnectionStatus::Disconnected => "Disconnected".to_string(),
+            ConnectionStatus::Error(e) => format!("Error ({})", e),
+        };
+        self.messages.push(ChatMessage::system(format!(
+            "🪪 **Who am I**\n\n\
+            User: {}\n\
+            Session: {}\n\
+            Backend: {} ({})\n\
+            Status: {}\n\
+            Messages: {}\n\
+            Live tool calls: {}",
+            self.current_user_id,
+            self.session_id,
+            self.api_client.get_base_url(),
+            self.backend_version.as_deref().unwrap_or("version unknown"),
+            status,
+            self.messages.len(),
+            self.live_tool_calls.len()
+        )));
+    }
+
+    fn handle_last_request_command(&mut self) {
+        let Some((request, url)) = &self.last_request else {
+            self.messages
+                .push(ChatMessage::system("No message has been sent yet."));
+            return;
+        };
+        let payload = serde_json::to_string_pretty(request).unwrap_or_default();
+        self.messages.push(ChatMessage::system(format!(
+            "📤 **Last Request**\n\n```json\n{}\n```\n\nSent as: `GET {}`",
+            payload, url
+        )));
+    }
+
+    fn handle_history_command(&mut self) {
+        let text = match sessions::list_sessions(&self.current_user_id) {
+            Ok(list) if list.is_empty() => {
+                format!("No saved sessions for {}.", self.current_user_id)
+            }
+            Ok(list) => {
+                let mut text = format!("🗂 **Saved Sessions** ({})\n\n", self.current_user_id);
+                for summary in list {
+                    let updated = chrono::DateTime::parse_from_rfc3339(&summary.updated_at)
+                        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or(summary.updated_at);
+                    let branch = summary
+                        .meta
+                        .branched_from
+                        .map(|parent| format!("  (branch of {})", parent))
+                        .unwrap_or_default();
+                    text.push_str(&format!(
+                        "{}  {}  {}{}\n",
+                        updated,
+                        summary.session_id,
+                        summary.meta.title.unwrap_or(summary.preview),
+                        branch
+                    ));
+                }
+                text.push_str("\nUse /load <session_id> to restore one.");
+                text
+            }
+            Err(e) => {
+                let text = format!("⚠️ Could not list sessions: {}", e);
+                self.log_error(text.clone());
+                text
+            }
+        };
+        self.messages.push(ChatMessage::system(text));
+    }
+
+    fn handle_load_command(&mut self, session_id: String) {
+        if self.is_loading {
+            self.messages.push(ChatMessage::system(
+                "Wait for the current response to finish before loading a session.",
+            ));
+            return;
+        }
+        match sessions::load_session(&self.current_user_id, &session_id) {
+            Ok(loaded) => {
+                self.session_id = session_id.clone();
+                self.session_meta = loaded.meta;
+                // Sessions saved before titles existed get one now
+                if self.session_meta.title.is_none() {
+                    self.session_meta.title = loaded
+                        .messages
+                        .iter()
+                        .find(|m| m.role == MessageRole::User)
+                        .map(|m| sessions::session_title(&m.content));
+                }
+                self.messages = loaded.messages;
+                self.live_tool_calls.clear();
+                self.expanded_tool_groups.clear();
+                self.current_tool_calls.clear();
+                self.messages.push(ChatMessage::system(format!(
+                    "Loaded session {}.",
+                    session_id
+                )));
+            }
+            Err(e) => {
+                self.report_error(format!(
+                    "⚠️ Could not load session {}: {}",
+                    session_id, e
+                ));
+            }
+        }
+    }
+
     fn handle_stats_command(&mut self, cx: &mut Context<Self>) {
         let api_client = self.api_client.clone();
         self.messages.push(ChatMessage::system("Fetching stats..."));
@@ -316,7 +1708,7 @@ impl App {
         .detach();
     }
 
-    fn handle_memory_command(&mut self, cx: &mut Context<Self>) {
+    fn handle_memory_command(&mut self, raw: bool, cx: &mut Context<Self>) {
         let api_client = self.api_client.clone();
         let user_id = self.current_user_id.clone();
         self.messages.push(ChatMessage::system("Fetching memory stats..."));
@@ -326,7 +1718,9 @@ impl App {
             let result = cx.background_spawn(async move { api_client.get_memory_stats(&user_id) }).await;
 
             let mut mem_text = String::from("🧠 **Memory Statistics** (per-user)\n\n");
-            if let Ok(stats) = result {
+            if let (true, Ok(stats)) = (raw, &result) {
+                mem_text = raw_json_block(stats);
+            } else if let Ok(stats) = &result {
                 mem_text.push_str(&format!("L1 Working: {} nodes\n", stats.l1_count));
                 mem_text.push_str(&format!("L2 Vector: {} nodes\n", stats.l2_vector_count));
                 mem_text.push_str(&format!(
@@ -348,7 +1742,7 @@ impl App {
         .detach();
     }
 
-    fn handle_knowledge_command(&mut self, cx: &mut Context<Self>) {
+    fn handle_knowledge_command(&mut self, raw: bool, cx: &mut Context<Self>) {
         let api_client = self.api_client.clone();
         self.messages.push(ChatMessage::system("Fetching knowledge stats..."));
         cx.notify();
@@ -357,7 +1751,9 @@ impl App {
             let result = cx.background_spawn(async move { api_client.get_knowledge_stats() }).await;
 
             let mut know_text = String::from("📚 **Knowledge Statistics** (global, shared)\n\n");
-            if let Ok(stats) = result {
+            if let (true, Ok(stats)) = (raw, &result) {
+                know_text = raw_json_block(stats);
+            } else if let Ok(stats) = &result {
                 if stats.status.as_deref() == Some("unavailable") {
                     know_text.push_str("Knowledge system not available");
                 } else {
@@ -385,41 +1781,38 @@ impl App {
         let session_id = self.session_id.clone();
 
         cx.spawn(async move |this, cx| {
-            let _ = cx
+            let result = cx
                 .background_spawn(async move { api_client.clear_chat(Some(session_id)) })
                 .await;
 
             let _ = this.update(cx, |app, cx| {
                 app.messages.clear();
-                app.messages.push(ChatMessage::system("Conversation cleared."));
                 app.current_tool_calls.clear();
+                // The next message names the emptied session afresh
+                app.session_meta.title = None;
+                match clear_notice(result) {
+                    Ok(text) => app.messages.push(ChatMessage::system(text)),
+                    Err(text) => app.report_error(text),
+                }
                 cx.notify();
             });
         })
         .detach();
     }
 
-    /// Filter out tool call patterns from LLM response to keep it clean
+    /// Filter out tool call patterns from LLM response to keep it clean.
+    ///
+    /// Only strips text that is unambiguously injected tool output: a line that
+    /// is exactly `🔧 **name**` (optionally followed by an italic progress line
+    /// and a fenced block) and the `[Summarized from N chars]` marker. Prose that
+    /// merely mentions a tool or uses `_emphasis..._` is left alone.
     pub fn clean_response_content(content: &str) -> String {
         let mut result = content.to_string();
 
-        let tool_block_patterns = [
-            r"(?s)🔧\s*\*\*\w+\*\*.*?```[\s\S]*?```",
-            r"🔧\s*\*\*\w+\*\*\s*\n_[^_]*\.\.\._",
-            r"🔧\s*\*\*\w+\*\*",
-            r"\[Summarized from \d+ chars\]",
-            r"_[^_\n]+\.\.\._",
-        ];
-
-        for pattern in tool_block_patterns {
-            if let Ok(re) = Regex::new(pattern) {
-                result = re.replace_all(&result, "").to_string();
-            }
-        }
-
-        if let Ok(re) = Regex::new(r"```\s*\n?\s*```") {
+        for re in TOOL_OUTPUT_PATTERNS.iter() {
             result = re.replace_all(&result, "").to_string();
         }
+        result = EMPTY_FENCE.replace_all(&result, "").to_string();
 
         while result.contains("\n\n\n") {
             result = result.replace("\n\n\n", "\n\n");
@@ -428,3 +1821,53 @@ impl App {
         result.trim().to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear_notice_confirms_a_successful_clear() {
+        assert_eq!(clear_notice(Ok(())), Ok("Conversation cleared.".to_string()));
+    }
+
+    #[test]
+    fn clear_notice_reports_a_backend_failure_with_a_retry_hint() {
+        let error = clear_notice(Err(anyhow::anyhow!("HTTP 500: database unavailable")))
+            .unwrap_err();
+        assert!(error.starts_with("⚠️ Cleared locally"));
+        assert!(error.contains("HTTP 500: database unavailable"));
+        assert!(error.contains("Run /clear again to retry."));
+    }
+
+    #[test]
+    fn clean_response_content_strips_injected_tool_output() {
+        let content = "Let me check.\n🔧 **read_file**\n_Reading file..._\n```\nfn main() {}\n```\nThe file defines main.";
+        assert_eq!(
+            App::clean_response_content(content),
+            "Let me check.\n\nThe file defines main."
+        );
+
+        let content = "Searching.\n🔧 **web_search**\n_Searching the web..._\nFound three results.";
+        assert_eq!(
+            App::clean_response_content(content),
+            "Searching.\n\nFound three results."
+        );
+
+        let content = "Summary below. [Summarized from 4096 chars]";
+        assert_eq!(App::clean_response_content(content), "Summary below.");
+    }
+
+    #[test]
+    fn clean_response_content_keeps_ordinary_prose_and_markdown() {
+        for content in [
+            "The page shows _loading..._ while it waits.",
+            "_loading..._",
+            "Run it like this:\n\n```bash\ncargo run --release\n```\n\nThen open the window.",
+            "- **Note**: restart the server\n- 🔧 **Fix**: clear the cache",
+            "🔧 **Tip** — keep your tools sharp.",
+        ] {
+            assert_eq!(App::clean_response_content(content), content);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Icon strings for OmniEmployee GUI
+//!
+//! Emoji render as empty boxes on systems without an emoji font, so every
+//! icon has an ASCII stand-in, picked with the `ascii_mode` config key.
+
+use crate::models::ToolStatus;
+
+/// One full set of icons
+pub struct Icons {
+    pub bot: &'static str,
+    pub tool: &'static str,
+    pub running: &'static str,
+    pub ok: &'static str,
+    pub failed: &'static str,
+    pub warning: &'static str,
+    pub memory: &'static str,
+    pub knowledge: &'static str,
+    pub skills: &'static str,
+    pub reasoning: &'static str,
+    pub user: &'static str,
+    pub backend: &'static str,
+    pub settings: &'static str,
+}
+
+const EMOJI: Icons = Icons {
+    bot: "🤖",
+    tool: "🔧",
+    running: "⏳",
+    ok: "✅",
+    failed: "❌",
+    warning: "⚠️",
+    memory: "🧠",
+    knowledge: "📚",
+    skills: "🧩",
+    reasoning: "💭",
+    user: "👤",
+    backend: "🌐",
+    settings: "⚙",
+};
+
+const ASCII: Icons = Icons {
+    bot: "[bot]",
+    tool: "[tool]",
+    running: "[...]",
+    ok: "[ok]",
+    failed: "[x]",
+    warning: "[!]",
+    memory: "[mem]",
+    knowledge: "[kb]",
+    skills: "[skills]",
+    reasoning: "[think]",
+    user: "[user]",
+    backend: "[url]",
+    settings: "[cfg]",
+};
+
+impl Icons {
+    pub fn get(ascii_mode: bool) -> &'static Icons {
+        if ascii_mode {
+            &ASCII
+        } else {
+            &EMOJI
+        }
+    }
+
+    pub fn tool_status(&self, status: &ToolStatus) -> &'static str {
+        match status {
+            ToolStatus::Running => self.running,
+            ToolStatus::Completed => self.ok,
+            ToolStatus::Failed => self.failed,
+        }
+    }
+}
@@ -5,8 +5,12 @@
 mod api;
 mod app;
 mod components;
+mod debug_log;
+mod export;
 mod handlers;
+mod icons;
 mod models;
+mod sessions;
 mod theme;
 
 use app::App;
@@ -20,6 +24,8 @@ fn main() {
     Application::new().run(|cx| {
         // Initialize gpui-component (required before using any component)
         gpui_component::init(cx);
+        app::bind_app_keys(cx);
+        components::bind_message_keys(cx);
 
         let window_options = WindowOptions {
             window_bounds: Some(WindowBounds::Windowed(Bounds::centered(
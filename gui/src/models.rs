@@ -1,6 +1,17 @@
 //! Data models for OmniEmployee GUI
 
-use crate::api::ToolCall;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{KnowledgeChange, ToolCall};
 
 /// Message role in conversation
 #[derive(Debug, Clone, PartialEq)]
@@ -10,6 +21,25 @@ pub enum MessageRole {
     System,
 }
 
+impl MessageRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+            MessageRole::System => "system",
+        }
+    }
+
+    /// Parse a stored role name, treating anything unknown as System
+    pub fn parse(role: &str) -> Self {
+        match role {
+            "user" => MessageRole::User,
+            "assistant" => MessageRole::Assistant,
+            _ => MessageRole::System,
+        }
+    }
+}
+
 /// Inline tool call for streaming display
 #[derive(Debug, Clone)]
 pub struct InlineToolCall {
@@ -19,6 +49,9 @@ pub struct InlineToolCall {
     pub result: Option<String>,
     pub status: ToolStatus,
     pub expanded: bool,
+    /// When the ToolStart event arrived (transient, not serialized)
+    pub started_at: Instant,
+    pub duration_ms: Option<u64>,
 }
 
 /// A segment of message content (text or tool call)
@@ -34,10 +67,32 @@ pub struct ChatMessage {
     pub id: String,
     pub role: MessageRole,
     pub content: String,
-    pub timestamp: String,
+    pub timestamp: DateTime<Local>,
     pub tool_calls: Vec<ToolCall>,
     /// Segments for inline display (text interspersed with tool calls)
     pub segments: Vec<MessageSegment>,
+    /// Recoverable stream error shown under the partial reply
+    pub stream_error: Option<String>,
+    /// Set when a fatal stream error turned this reply into a System message
+    pub failed: bool,
+    /// Reasoning streamed before/alongside the answer (shown collapsed)
+    pub reasoning: String,
+    pub reasoning_expanded: bool,
+    /// User whose memory answered this reply, when sent with "Ask as..."
+    pub answered_as: Option<String>,
+    /// Sources cited in the reply, numbered by position (footnote 1 = index 0)
+    pub citations: Vec<Citation>,
+    /// Body hidden behind a one-line summary (per message, not saved)
+    pub collapsed: bool,
+}
+
+/// A source cited by the assistant
+#[derive(Debug, Clone, PartialEq)]
+pub struct Citation {
+    pub id: String,
+    pub source: String,
+    pub title: String,
+    pub url: Option<String>,
 }
 
 impl ChatMessage {
@@ -47,9 +102,16 @@ impl ChatMessage {
             id: uuid::Uuid::new_v4().to_string(),
             role: MessageRole::User,
             content: content_str.clone(),
-            timestamp: chrono::Local::now().format("%H:%M").to_string(),
+            timestamp: Local::now(),
             tool_calls: vec![],
             segments: vec![MessageSegment::Text(content_str)],
+            stream_error: None,
+            failed: false,
+            reasoning: String::new(),
+            reasoning_expanded: false,
+            answered_as: None,
+            citations: vec![],
+            collapsed: false,
         }
     }
 
@@ -59,9 +121,16 @@ impl ChatMessage {
             id: uuid::Uuid::new_v4().to_string(),
             role: MessageRole::Assistant,
             content: content_str.clone(),
-            timestamp: chrono::Local::now().format("%H:%M").to_string(),
+            timestamp: Local::now(),
             tool_calls,
             segments: vec![MessageSegment::Text(content_str)],
+            stream_error: None,
+            failed: false,
+            reasoning: String::new(),
+            reasoning_expanded: false,
+            answered_as: None,
+            citations: vec![],
+            collapsed: false,
         }
     }
 
@@ -71,9 +140,16 @@ impl ChatMessage {
             id: uuid::Uuid::new_v4().to_string(),
             role: MessageRole::System,
             content: content_str.clone(),
-            timestamp: chrono::Local::now().format("%H:%M").to_string(),
+            timestamp: Local::now(),
             tool_calls: vec![],
             segments: vec![MessageSegment::Text(content_str)],
+            stream_error: None,
+            failed: false,
+            reasoning: String::new(),
+            reasoning_expanded: false,
+            answered_as: None,
+            citations: vec![],
+            collapsed: false,
         }
     }
 
@@ -83,12 +159,37 @@ impl ChatMessage {
             id: uuid::Uuid::new_v4().to_string(),
             role: MessageRole::Assistant,
             content: String::new(),
-            timestamp: chrono::Local::now().format("%H:%M").to_string(),
+            timestamp: Local::now(),
             tool_calls: vec![],
             segments: vec![],
+            stream_error: None,
+            failed: false,
+            reasoning: String::new(),
+            reasoning_expanded: false,
+            answered_as: None,
+            citations: vec![],
+            collapsed: false,
         }
     }
 
+    /// Turn a finished reply with no text and no tool calls into a failed note,
+    /// so it can be regenerated instead of leaving a blank bubble. Tool-only
+    /// replies are legitimately textless and are left alone.
+    pub fn mark_if_empty_response(&mut self) {
+        let has_tools = !self.tool_calls.is_empty()
+            || self
+                .segments
+                .iter()
+                .any(|seg| matches!(seg, MessageSegment::ToolCall(_)));
+        if has_tools || !self.content.trim().is_empty() {
+            return;
+        }
+        self.role = MessageRole::System;
+        self.content = EMPTY_RESPONSE_NOTE.to_string();
+        self.segments = vec![MessageSegment::Text(self.content.clone())];
+        self.failed = true;
+    }
+
     /// Append text to the last text segment or create a new one
     pub fn append_text(&mut self, text: &str) {
         self.content.push_str(text);
@@ -99,6 +200,19 @@ impl ChatMessage {
         }
     }
 
+    /// Record a citation and mark the text streamed so far with its footnote
+    /// number; a repeated citation id reuses its number
+    pub fn add_citation(&mut self, citation: Citation) {
+        let number = match self.citations.iter().position(|c| c.id == citation.id) {
+            Some(index) => index + 1,
+            None => {
+                self.citations.push(citation);
+                self.citations.len()
+            }
+        };
+        self.append_text(&superscript_number(number));
+    }
+
     /// Add a tool call segment
     pub fn add_tool_call(&mut self, tool: InlineToolCall) {
         self.segments.push(MessageSegment::ToolCall(tool));
@@ -111,12 +225,53 @@ impl ChatMessage {
                 if tc.id == tool_id {
                     tc.result = Some(result);
                     tc.status = status;
+                    tc.duration_ms = Some(tc.started_at.elapsed().as_millis() as u64);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Mark a tool call as running again (e.g. when retried)
+    pub fn restart_tool(&mut self, tool_id: &str) {
+        for seg in &mut self.segments {
+            if let MessageSegment::ToolCall(ref mut tc) = seg {
+                if tc.id == tool_id {
+                    tc.status = ToolStatus::Running;
+                    tc.started_at = Instant::now();
+                    tc.duration_ms = None;
                     break;
                 }
             }
         }
     }
 
+    /// Mark any still-running tool calls as failed (the stream ended under them)
+    pub fn fail_running_tools(&mut self) {
+        for seg in &mut self.segments {
+            if let MessageSegment::ToolCall(ref mut tc) = seg {
+                if tc.status == ToolStatus::Running {
+                    tc.status = ToolStatus::Failed;
+                    tc.duration_ms = Some(tc.started_at.elapsed().as_millis() as u64);
+                }
+            }
+        }
+    }
+
+    /// Finalize a reply whose stream ended without Done/Error: keep any partial
+    /// answer with the error under it, or turn an empty reply into a failed note
+    pub fn finish_dropped_stream(&mut self, error: String) {
+        self.fail_running_tools();
+        if self.segments.is_empty() && self.content.is_empty() {
+            self.content = format!("⚠️ Error: {}", error);
+            self.role = MessageRole::System;
+            self.failed = true;
+        } else {
+            self.rebuild_content();
+            self.stream_error = Some(error);
+        }
+    }
+
     /// Toggle tool call expansion by id
     pub fn toggle_tool_expanded(&mut self, tool_id: &str) {
         for seg in &mut self.segments {
@@ -141,16 +296,96 @@ impl ChatMessage {
     }
 }
 
+/// A block of rendered message content
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentBlock {
+    Markdown(String),
+    Code { language: String, code: String },
+}
+
+/// Split markdown text into plain markdown and fenced code blocks.
+///
+/// Unterminated fences (e.g. while a reply is still streaming) stay in the
+/// markdown so they render as soon as the closing fence arrives.
+pub fn split_code_blocks(text: &str) -> Vec<ContentBlock> {
+    let mut blocks = Vec::new();
+    let mut markdown = String::new();
+    let mut lines = text.lines();
+
+    while let Some(line) = lines.next() {
+        let Some(fence_rest) = line.trim_start().strip_prefix("```") else {
+            markdown.push_str(line);
+            markdown.push('\n');
+            continue;
+        };
+
+        let language = fence_rest.trim().to_string();
+        let mut code_lines = Vec::new();
+        let mut closed = false;
+        for code_line in lines.by_ref() {
+            if code_line.trim_start().starts_with("```") {
+                closed = true;
+                break;
+            }
+            code_lines.push(code_line);
+        }
+
+        if !closed {
+            markdown.push_str(line);
+            markdown.push('\n');
+            for code_line in code_lines {
+                markdown.push_str(code_line);
+                markdown.push('\n');
+            }
+            continue;
+        }
+
+        if !markdown.trim().is_empty() {
+            blocks.push(ContentBlock::Markdown(std::mem::take(&mut markdown)));
+        }
+        markdown.clear();
+        blocks.push(ContentBlock::Code {
+            language,
+            code: code_lines.join("\n"),
+        });
+    }
+
+    if !markdown.trim().is_empty() {
+        blocks.push(ContentBlock::Markdown(markdown));
+    }
+    blocks
+}
+
 /// Command types for /commands
 #[derive(Debug, Clone)]
 pub enum Command {
     Stats,
-    Memory,
-    Knowledge,
+    /// `raw` dumps the stats as JSON instead of formatted text
+    Memory { raw: bool },
+    Knowledge { raw: bool },
     Help,
     Clear,
     Reconnect,
-    Config { key: String, value: String },
+    /// `/config` lists every key, `/config <key>` shows one, `/config <key> <value>` sets it
+    Config {
+        key: Option<String>,
+        value: Option<String>,
+    },
+    Model { name: String },
+    Provider { name: Option<String> },
+    History,
+    Load { session_id: String },
+    Filter { role: String, hidden: bool },
+    System { prompt: String },
+    WhoAmI,
+    Rename { new_name: String },
+    LastRequest,
+    Errors,
+    Export { format: String, path: String },
+    Profiles,
+    Profile { name: String },
+    ProfileAdd { name: String, url: String },
+    ProfileRemove { name: String },
     Unknown(String),
 }
 
@@ -168,26 +403,371 @@ impl Command {
 
         match parts[0].to_lowercase().as_str() {
             "stats" => Some(Command::Stats),
-            "memory" => Some(Command::Memory),
-            "knowledge" => Some(Command::Knowledge),
+            "memory" => Some(Command::Memory {
+                raw: parts.get(1) == Some(&"raw"),
+            }),
+            "knowledge" => Some(Command::Knowledge {
+                raw: parts.get(1) == Some(&"raw"),
+            }),
             "help" | "h" | "?" => Some(Command::Help),
             "clear" => Some(Command::Clear),
             "reconnect" => Some(Command::Reconnect),
-            "config" if parts.len() >= 3 => Some(Command::Config {
-                key: parts[1].to_string(),
-                value: parts[2].to_string(),
+            "config" => Some(Command::Config {
+                key: parts.get(1).map(|key| key.to_string()),
+                value: parts.get(2).map(|value| value.to_string()),
+            }),
+            "model" if parts.len() >= 2 => Some(Command::Model {
+                name: parts[1].to_string(),
+            }),
+            "provider" => Some(Command::Provider {
+                name: parts.get(1).map(|name| name.to_string()),
+            }),
+            "whoami" => Some(Command::WhoAmI),
+            "rename" => Some(Command::Rename {
+                new_name: parts.get(1).map(|name| name.to_string()).unwrap_or_default(),
+            }),
+            "lastrequest" => Some(Command::LastRequest),
+            "errors" => Some(Command::Errors),
+            "profile" | "profiles" => Some(match (parts.get(1), parts.get(2), parts.get(3)) {
+                // Missing arguments come through empty so the handler can show usage
+                (Some(&"add"), name, url) => Command::ProfileAdd {
+                    name: name.map(|name| name.to_string()).unwrap_or_default(),
+                    url: url.map(|url| url.trim_end_matches('/').to_string()).unwrap_or_default(),
+                },
+                (Some(&"remove"), name, _) => Command::ProfileRemove {
+                    name: name.map(|name| name.to_string()).unwrap_or_default(),
+                },
+                (Some(name), None, _) => Command::Profile {
+                    name: name.to_string(),
+                },
+                _ => Command::Profiles,
+            }),
+            // The path may contain spaces, so take everything after the format
+            "export" => Some(Command::Export {
+                format: parts.get(1).map(|f| f.to_lowercase()).unwrap_or_default(),
+                path: input[1..]
+                    .splitn(3, char::is_whitespace)
+                    .nth(2)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            }),
+            "history" => Some(Command::History),
+            "load" if parts.len() >= 2 => Some(Command::Load {
+                session_id: parts[1].to_string(),
+            }),
+            "filter" if parts.len() >= 3 => Some(Command::Filter {
+                role: parts[1].to_lowercase(),
+                hidden: matches!(parts[2].to_lowercase().as_str(), "on" | "true" | "hide"),
+            }),
+            // Keep the prompt's own spacing; an empty prompt clears it
+            "system" => Some(Command::System {
+                prompt: input[1..]
+                    .split_once(char::is_whitespace)
+                    .map(|(_, rest)| rest.trim().to_string())
+                    .unwrap_or_default(),
             }),
             cmd => Some(Command::Unknown(cmd.to_string())),
         }
     }
 }
 
-/// App configuration
-#[derive(Clone)]
+/// Directory holding the GUI's persisted files
+pub fn app_config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("omniemployee"))
+}
+
+/// Message spacing
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+impl Density {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Density::Comfortable => "comfortable",
+            Density::Compact => "compact",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "comfortable" => Some(Density::Comfortable),
+            "compact" => Some(Density::Compact),
+            _ => None,
+        }
+    }
+
+    pub fn toggled(&self) -> Self {
+        match self {
+            Density::Comfortable => Density::Compact,
+            Density::Compact => Density::Comfortable,
+        }
+    }
+}
+
+/// What an expanded tool call shows
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolDisplayMode {
+    Args,
+    #[default]
+    Result,
+    Both,
+}
+
+impl ToolDisplayMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ToolDisplayMode::Args => "args",
+            ToolDisplayMode::Result => "result",
+            ToolDisplayMode::Both => "both",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "args" | "arguments" => Some(ToolDisplayMode::Args),
+            "result" => Some(ToolDisplayMode::Result),
+            "both" => Some(ToolDisplayMode::Both),
+            _ => None,
+        }
+    }
+
+    pub fn shows_args(&self) -> bool {
+        matches!(self, ToolDisplayMode::Args | ToolDisplayMode::Both)
+    }
+
+    pub fn shows_result(&self) -> bool {
+        matches!(self, ToolDisplayMode::Result | ToolDisplayMode::Both)
+    }
+}
+
+/// How message timestamps are shown
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampFormat {
+    /// Time of day only
+    #[default]
+    Time,
+    /// Date and time
+    Date,
+    /// "5m ago", "2h ago", ...
+    Relative,
+}
+
+impl TimestampFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimestampFormat::Time => "time",
+            TimestampFormat::Date => "date",
+            TimestampFormat::Relative => "relative",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "time" => Some(TimestampFormat::Time),
+            "date" => Some(TimestampFormat::Date),
+            "relative" => Some(TimestampFormat::Relative),
+            _ => None,
+        }
+    }
+}
+
+/// Format a message timestamp for display
+pub fn format_timestamp(
+    timestamp: &DateTime<Local>,
+    format: TimestampFormat,
+    clock_12h: bool,
+) -> String {
+    let time = if clock_12h { "%-I:%M %p" } else { "%H:%M" };
+    match format {
+        TimestampFormat::Time => timestamp.format(time).to_string(),
+        TimestampFormat::Date => timestamp.format(&format!("%Y-%m-%d {}", time)).to_string(),
+        TimestampFormat::Relative => {
+            let elapsed = Local::now().signed_duration_since(*timestamp);
+            match elapsed.num_minutes() {
+                ..=0 => "just now".to_string(),
+                minutes @ 1..=59 => format!("{}m ago", minutes),
+                _ if elapsed.num_hours() < 24 => format!("{}h ago", elapsed.num_hours()),
+                _ => format!("{}d ago", elapsed.num_days()),
+            }
+        }
+    }
+}
+
+/// Ordering of the sidebar Knowledge panel
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum KnowledgeSort {
+    /// Most confident first
+    #[default]
+    Confidence,
+    Subject,
+    Source,
+}
+
+impl KnowledgeSort {
+    pub fn label(&self) -> &'static str {
+        match self {
+            KnowledgeSort::Confidence => "confidence",
+            KnowledgeSort::Subject => "subject",
+            KnowledgeSort::Source => "source",
+        }
+    }
+
+    /// Next option, for a click-to-cycle control
+    pub fn next(&self) -> Self {
+        match self {
+            KnowledgeSort::Confidence => KnowledgeSort::Subject,
+            KnowledgeSort::Subject => KnowledgeSort::Source,
+            KnowledgeSort::Source => KnowledgeSort::Confidence,
+        }
+    }
+}
+
+/// Which calls the sidebar Tool Use panel shows
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToolFilter {
+    #[default]
+    All,
+    Failed,
+    Running,
+}
+
+impl ToolFilter {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ToolFilter::All => "all",
+            ToolFilter::Failed => "failed",
+            ToolFilter::Running => "running",
+        }
+    }
+
+    /// Next option, for a click-to-cycle control
+    pub fn next(&self) -> Self {
+        match self {
+            ToolFilter::All => ToolFilter::Failed,
+            ToolFilter::Failed => ToolFilter::Running,
+            ToolFilter::Running => ToolFilter::All,
+        }
+    }
+
+    pub fn matches(&self, status: &ToolStatus) -> bool {
+        match self {
+            ToolFilter::All => true,
+            ToolFilter::Failed => *status == ToolStatus::Failed,
+            ToolFilter::Running => *status == ToolStatus::Running,
+        }
+    }
+}
+
+/// Whether the Tool Use panel covers the latest response or the whole conversation
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToolScope {
+    #[default]
+    Response,
+    Session,
+}
+
+impl ToolScope {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ToolScope::Response => "this response",
+            ToolScope::Session => "session",
+        }
+    }
+
+    /// Next option, for a click-to-cycle control
+    pub fn next(&self) -> Self {
+        match self {
+            ToolScope::Response => ToolScope::Session,
+            ToolScope::Session => ToolScope::Response,
+        }
+    }
+}
+
+/// "Knowledge updated" toast shown after a reply taught the agent something
+#[derive(Debug, Clone)]
+pub struct KnowledgeToast {
+    /// Distinguishes toasts so an old dismiss timer can't close a newer one
+    pub id: String,
+    pub added: Vec<KnowledgeChange>,
+    pub updated: Vec<KnowledgeChange>,
+}
+
+/// A named backend the GUI can switch between
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub url: String,
+}
+
+/// Shown in place of a reply that finished without any text
+pub const EMPTY_RESPONSE_NOTE: &str =
+    "⚠️ The assistant returned an empty response. Try regenerating or rephrasing.";
+
+/// Allowed range for `max_message_width`, in pixels
+pub const MIN_MESSAGE_WIDTH: f32 = 400.;
+pub const MAX_MESSAGE_WIDTH: f32 = 1400.;
+
+/// Allowed range for the sidebar panel heights, in pixels
+pub const MIN_PANEL_HEIGHT: f32 = 100.;
+pub const MAX_PANEL_HEIGHT: f32 = 1200.;
+
+/// App configuration (persisted to the user config dir)
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub show_memory: bool,
     pub show_knowledge: bool,
     pub show_tool_use: bool,
+    /// Render message content as markdown (false = plain monospace text)
+    pub render_markdown: bool,
+    pub density: Density,
+    pub timestamp_format: TimestampFormat,
+    /// Show times as 12-hour ("3:05 PM") instead of 24-hour
+    pub clock_12h: bool,
+    /// What expanded tool calls show: arguments, result or both
+    pub tool_display: ToolDisplayMode,
+    /// While streaming, collapse all but the latest live tool call to one line
+    pub compact_during_stream: bool,
+    /// Replace emoji icons with ASCII text (for systems without an emoji font)
+    pub ascii_mode: bool,
+    /// Style completed tool results that read like errors as failures
+    pub detect_tool_errors: bool,
+    /// Open panels and tool calls instantly instead of animating them
+    pub reduce_motion: bool,
+    /// Play a sound when a reply finishes while the window is in the background
+    pub notify_on_complete: bool,
+    /// Clear the conversation view when switching users
+    pub clear_on_user_switch: bool,
+    /// Maximum message bubble width in pixels (compact density widens it by half)
+    pub max_message_width: f32,
+    /// Tallest the Memory and Knowledge panels grow before scrolling, in pixels
+    pub panel_max_height: f32,
+    /// Tallest the Tool Use panel grows before scrolling, in pixels
+    pub tool_panel_max_height: f32,
+    /// Write API requests/responses to the debug log
+    pub debug: bool,
+    /// Longest message that may be sent, in characters (0 = off)
+    pub max_input_chars: usize,
+    /// Estimated conversation tokens before warning (0 = off)
+    pub context_warn_threshold: usize,
+    /// Seconds between live stats refreshes (0 = off)
+    pub stats_interval_secs: u64,
+    /// Accent color overrides as hex, keyed by theme field (`accent_cyan`, ...)
+    pub accent_colors: BTreeMap<String, String>,
+    /// Saved backends for quick switching
+    pub profiles: Vec<Profile>,
+    /// Profile connected to on startup (None = default backend URL)
+    pub active_profile: Option<String>,
+    /// Keys the user set explicitly; these win over backend-reported defaults
+    pub explicit_keys: Vec<String>,
 }
 
 impl Default for AppConfig {
@@ -196,8 +776,65 @@ impl Default for AppConfig {
             show_memory: true,
             show_knowledge: true,
             show_tool_use: true,
+            render_markdown: true,
+            density: Density::Comfortable,
+            timestamp_format: TimestampFormat::Time,
+            clock_12h: false,
+            tool_display: ToolDisplayMode::Result,
+            compact_during_stream: false,
+            ascii_mode: false,
+            detect_tool_errors: true,
+            reduce_motion: false,
+            notify_on_complete: false,
+            clear_on_user_switch: true,
+            max_message_width: 600.,
+            panel_max_height: 200.,
+            tool_panel_max_height: 400.,
+            debug: false,
+            max_input_chars: 20_000,
+            context_warn_threshold: 100_000,
+            stats_interval_secs: 0,
+            accent_colors: BTreeMap::new(),
+            profiles: vec![],
+            active_profile: None,
+            explicit_keys: vec![],
+        }
+    }
+}
+
+impl AppConfig {
+    fn path() -> Option<PathBuf> {
+        app_config_dir().map(|dir| dir.join("gui_config.json"))
+    }
+
+    /// Load the saved config, falling back to defaults if missing or unreadable
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the config to disk
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow::anyhow!("no config directory available"))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record that the user set a key explicitly
+    pub fn mark_explicit(&mut self, key: &str) {
+        if !self.is_explicit(key) {
+            self.explicit_keys.push(key.to_string());
         }
     }
+
+    pub fn is_explicit(&self, key: &str) -> bool {
+        self.explicit_keys.iter().any(|k| k == key)
+    }
 }
 
 /// Connection status
@@ -209,6 +846,18 @@ pub enum ConnectionStatus {
     Error(String),
 }
 
+impl ConnectionStatus {
+    /// Short label for the header indicator and window title
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConnectionStatus::Connected => "Connected",
+            ConnectionStatus::Connecting => "Connecting...",
+            ConnectionStatus::Disconnected => "Disconnected",
+            ConnectionStatus::Error(_) => "Error",
+        }
+    }
+}
+
 /// Live tool call being displayed (for real-time updates)
 #[derive(Debug, Clone)]
 pub struct LiveToolCall {
@@ -218,6 +867,145 @@ pub struct LiveToolCall {
     pub result: Option<String>,
     pub status: ToolStatus,
     pub expanded: bool,
+    /// Set once the user toggles the row, so compact streaming leaves it alone
+    pub pinned: bool,
+    /// When the ToolStart event arrived (transient, not serialized)
+    pub started_at: Instant,
+    pub duration_ms: Option<u64>,
+}
+
+/// Index ranges of consecutive calls to the same tool, in order
+pub fn group_consecutive_tools(calls: &[LiveToolCall]) -> Vec<Range<usize>> {
+    let mut groups: Vec<Range<usize>> = Vec::new();
+    for (i, call) in calls.iter().enumerate() {
+        match groups.last_mut() {
+            Some(group) if calls[group.start].name == call.name => group.end = i + 1,
+            _ => groups.push(i..i + 1),
+        }
+    }
+    groups
+}
+
+/// Footnote marker, e.g. 12 → "¹²"
+pub fn superscript_number(number: usize) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    number
+        .to_string()
+        .chars()
+        .filter_map(|c| c.to_digit(10).map(|d| DIGITS[d as usize]))
+        .collect()
+}
+
+/// Format a tool duration for display, e.g. "350ms" or "1.2s"
+pub fn format_duration(duration_ms: u64) -> String {
+    if duration_ms < 1000 {
+        format!("{}ms", duration_ms)
+    } else {
+        format!("{:.1}s", duration_ms as f64 / 1000.0)
+    }
+}
+
+/// Where an image tool result points
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageReference {
+    /// `data:image/png;base64,...`
+    DataUri { mime: String, base64: String },
+    /// An http(s) URL ending in an image extension
+    Url(String),
+}
+
+impl ImageReference {
+    /// File extension for the image, used when saving it to open externally
+    pub fn extension(&self) -> String {
+        match self {
+            ImageReference::DataUri { mime, .. } => mime
+                .trim_start_matches("image/")
+                .trim_end_matches("+xml")
+                .replace("jpeg", "jpg"),
+            ImageReference::Url(url) => url
+                .split(['?', '#'])
+                .next()
+                .and_then(|path| path.rsplit('.').next())
+                .unwrap_or("png")
+                .to_lowercase(),
+        }
+    }
+}
+
+/// First non-blank line of `text`, cut to `max_chars` with an ellipsis
+pub fn one_line_preview(text: &str, max_chars: usize) -> String {
+    let first_line = text
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or_default()
+        .trim();
+    if first_line.chars().count() > max_chars {
+        let preview: String = first_line.chars().take(max_chars).collect();
+        format!("{}…", preview.trim_end())
+    } else {
+        first_line.to_string()
+    }
+}
+
+/// Cut `text` to at most `max_chars` characters, marking the cut with "...".
+/// Counts chars, not bytes: slicing mid-character panics on emoji/CJK output.
+pub fn truncate_chars(text: &str, max_chars: usize) -> String {
+    if text.chars().count() > max_chars {
+        let preview: String = text.chars().take(max_chars).collect();
+        format!("{}...", preview)
+    } else {
+        text.to_string()
+    }
+}
+
+/// A line that opens or ends an error report: `Error: …`, `ValueError: …`,
+/// `java.io.IOException: …`, `ERROR: …` or a Python traceback header
+static TOOL_ERROR_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^(?:[\w.]*(?:Error|Exception)|ERROR|Traceback \(most recent call last\)):")
+        .expect("valid tool error pattern")
+});
+
+/// Guess whether a tool result is really an error message or stack trace; some
+/// tools report failures as ordinary output. Only whole lines shaped like an
+/// error count, so results that merely mention "Exception" stay green.
+pub fn looks_like_tool_error(result: &str) -> bool {
+    TOOL_ERROR_LINE.is_match(result.trim_start())
+}
+
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "svg"];
+
+/// Detect a tool result that is just an image (a data URI or an image URL)
+pub fn detect_image_reference(result: &str) -> Option<ImageReference> {
+    let result = result.trim();
+    if let Some(rest) = result.strip_prefix("data:image/") {
+        let (mime, base64) = rest.split_once(";base64,")?;
+        return Some(ImageReference::DataUri {
+            mime: format!("image/{}", mime),
+            base64: base64.trim().to_string(),
+        });
+    }
+
+    let is_url = result.starts_with("http://") || result.starts_with("https://");
+    if !is_url || result.contains(char::is_whitespace) {
+        return None;
+    }
+    let path = result.split(['?', '#']).next().unwrap_or_default().to_lowercase();
+    IMAGE_EXTENSIONS
+        .iter()
+        .any(|ext| path.ends_with(&format!(".{}", ext)))
+        .then(|| ImageReference::Url(result.to_string()))
+}
+
+/// Image loaded for a tool result
+#[derive(Clone)]
+pub enum ToolImage {
+    Loading,
+    Ready {
+        image: Arc<gpui::Image>,
+        reference: ImageReference,
+    },
+    /// Couldn't load; the result is shown as text instead
+    Failed,
 }
 
 /// Tool execution status
@@ -227,3 +1015,126 @@ pub enum ToolStatus {
     Completed,
     Failed,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn running_tool(id: &str) -> InlineToolCall {
+        InlineToolCall {
+            id: id.to_string(),
+            name: "read_file".to_string(),
+            arguments: serde_json::Value::Null,
+            result: None,
+            status: ToolStatus::Running,
+            expanded: false,
+            started_at: Instant::now(),
+            duration_ms: None,
+        }
+    }
+
+    #[test]
+    fn finish_dropped_stream_keeps_a_partial_reply() {
+        let mut msg = ChatMessage::assistant_streaming();
+        msg.append_text("Here is what I found");
+        msg.add_tool_call(running_tool("t1"));
+
+        msg.finish_dropped_stream("the stream closed without a response".to_string());
+
+        assert_eq!(msg.role, MessageRole::Assistant);
+        assert!(!msg.failed);
+        assert_eq!(msg.content, "Here is what I found");
+        assert_eq!(
+            msg.stream_error.as_deref(),
+            Some("the stream closed without a response")
+        );
+        let Some(MessageSegment::ToolCall(tool)) = msg.segments.last() else {
+            panic!("tool segment should remain");
+        };
+        assert_eq!(tool.status, ToolStatus::Failed);
+        assert!(tool.duration_ms.is_some());
+    }
+
+    #[test]
+    fn finish_dropped_stream_fails_an_empty_reply() {
+        let mut msg = ChatMessage::assistant_streaming();
+
+        msg.finish_dropped_stream("connection reset".to_string());
+
+        assert_eq!(msg.role, MessageRole::System);
+        assert!(msg.failed);
+        assert_eq!(msg.content, "⚠️ Error: connection reset");
+        assert_eq!(msg.stream_error, None);
+    }
+
+    #[test]
+    fn looks_like_tool_error_flags_error_reports() {
+        for result in [
+            "Error: file not found",
+            "  ERROR: permission denied",
+            "Traceback (most recent call last):\n  File \"x.py\", line 1\nValueError: bad",
+            "Running step 2\nFileNotFoundError: [Errno 2] No such file",
+            "Exception: connection refused",
+            "java.lang.NullPointerException: name is null",
+        ] {
+            assert!(looks_like_tool_error(result), "{:?}", result);
+        }
+    }
+
+    #[test]
+    fn looks_like_tool_error_ignores_results_that_mention_errors() {
+        for result in [
+            "src/app.py:12:    except ValueError: pass",
+            "Found 3 matches for Exception in docs/",
+            "class ParseException extends RuntimeException {}",
+            "Error handling is covered in chapter 3.",
+            "Errors: 0, warnings: 2",
+            "All tests passed",
+        ] {
+            assert!(!looks_like_tool_error(result), "{:?}", result);
+        }
+    }
+
+    #[test]
+    fn profile_add_keeps_missing_arguments_empty() {
+        assert!(matches!(
+            Command::parse("/profile add work http://host:8765/"),
+            Some(Command::ProfileAdd { name, url }) if name == "work" && url == "http://host:8765"
+        ));
+        assert!(matches!(
+            Command::parse("/profile add work"),
+            Some(Command::ProfileAdd { name, url }) if name == "work" && url.is_empty()
+        ));
+        assert!(matches!(
+            Command::parse("/profile add"),
+            Some(Command::ProfileAdd { name, url }) if name.is_empty() && url.is_empty()
+        ));
+        assert!(matches!(
+            Command::parse("/profile remove"),
+            Some(Command::ProfileRemove { name }) if name.is_empty()
+        ));
+        assert!(matches!(Command::parse("/profile"), Some(Command::Profiles)));
+        assert!(matches!(
+            Command::parse("/profile work"),
+            Some(Command::Profile { name }) if name == "work"
+        ));
+    }
+
+    #[test]
+    fn truncate_chars_cuts_emoji_and_cjk_on_char_boundaries() {
+        let text = "日本語🎉".repeat(150);
+        assert_eq!(text.chars().count(), 600);
+
+        let truncated = truncate_chars(&text, 500);
+        assert_eq!(truncated.chars().count(), 503);
+        assert!(truncated.ends_with("..."));
+        assert!(text.starts_with(truncated.trim_end_matches("...")));
+    }
+
+    #[test]
+    fn truncate_chars_keeps_short_text() {
+        let text = "🎉".repeat(500);
+        assert_eq!(truncate_chars(&text, 500), text);
+        assert_eq!(truncate_chars("", 500), "");
+    }
+}
@@ -0,0 +1,267 @@
+//! Local session persistence for OmniEmployee GUI
+//!
+//! Conversations are saved per user as JSON files under the app config dir
+//! (`<config>/omniemployee/sessions/<user>/<session_id>.json`).
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::api::ToolCall;
+use crate::models::{
+    app_config_dir, one_line_preview, ChatMessage, MessageRole, MessageSegment, ToolStatus,
+};
+
+/// A message as stored on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMessage {
+    pub role: String,
+    pub content: String,
+    pub timestamp: String,
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// Longest automatic session title, in characters
+const TITLE_MAX_CHARS: usize = 40;
+
+/// Details about a session kept alongside its messages
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionMeta {
+    /// Short name derived from the first user message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Session this one was branched from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branched_from: Option<String>,
+}
+
+/// A saved conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_id: String,
+    pub user_id: String,
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub meta: SessionMeta,
+    pub messages: Vec<StoredMessage>,
+}
+
+/// Summary shown by /history
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub updated_at: String,
+    pub preview: String,
+    pub meta: SessionMeta,
+}
+
+/// A saved conversation restored by /load
+pub struct LoadedSession {
+    pub messages: Vec<ChatMessage>,
+    pub meta: SessionMeta,
+}
+
+/// Session title from a message: its first line, cut to a readable length
+pub fn session_title(text: &str) -> String {
+    one_line_preview(text, TITLE_MAX_CHARS)
+}
+
+impl StoredMessage {
+    pub fn from_message(msg: &ChatMessage) -> Self {
+        // Inline tool calls carry results; prefer them over the summary list
+        let inline_tools: Vec<ToolCall> = msg
+            .segments
+            .iter()
+            .filter_map(|seg| match seg {
+                MessageSegment::ToolCall(tc) => Some(ToolCall {
+                    name: tc.name.clone(),
+                    arguments: tc.arguments.clone(),
+                    result: tc.result.clone(),
+                    success: tc.status != ToolStatus::Failed,
+                }),
+                MessageSegment::Text(_) => None,
+            })
+            .collect();
+
+        Self {
+            role: msg.role.as_str().to_string(),
+            content: msg.content.clone(),
+            timestamp: msg.timestamp.to_rfc3339(),
+            tool_calls: if inline_tools.is_empty() {
+                msg.tool_calls.clone()
+            } else {
+                inline_tools
+            },
+        }
+    }
+
+    pub fn into_message(self, saved_at: DateTime<Local>) -> ChatMessage {
+        let mut msg = match MessageRole::parse(&self.role) {
+            MessageRole::User => ChatMessage::user(self.content),
+            MessageRole::Assistant => ChatMessage::assistant(self.content, self.tool_calls),
+            MessageRole::System => ChatMessage::system(self.content),
+        };
+        msg.timestamp = parse_stored_timestamp(&self.timestamp, saved_at);
+        msg
+    }
+}
+
+/// Timestamps are RFC 3339; older sessions stored only "%H:%M", which is
+/// placed on the day the session was last saved
+fn parse_stored_timestamp(text: &str, saved_at: DateTime<Local>) -> DateTime<Local> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(text) {
+        return timestamp.with_timezone(&Local);
+    }
+    NaiveTime::parse_from_str(text, "%H:%M")
+        .ok()
+        .and_then(|time| {
+            saved_at
+                .date_naive()
+                .and_time(time)
+                .and_local_timezone(Local)
+                .single()
+        })
+        .unwrap_or(saved_at)
+}
+
+/// Make an id safe to use as a file name (no separators or `..`)
+fn safe_name(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+fn sessions_dir(user_id: &str) -> Option<PathBuf> {
+    app_config_dir().map(|dir| dir.join("sessions").join(safe_name(user_id)))
+}
+
+fn session_path(user_id: &str, session_id: &str) -> Option<PathBuf> {
+    sessions_dir(user_id).map(|dir| dir.join(format!("{}.json", safe_name(session_id))))
+}
+
+/// Save a conversation, skipping sessions with no user messages
+pub fn save_session(
+    user_id: &str,
+    session_id: &str,
+    meta: &SessionMeta,
+    messages: &[ChatMessage],
+) -> Result<()> {
+    if !messages.iter().any(|m| m.role == MessageRole::User) {
+        return Ok(());
+    }
+    let path = session_path(user_id, session_id)
+        .ok_or_else(|| anyhow::anyhow!("no config directory available"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let record = SessionRecord {
+        session_id: session_id.to_string(),
+        user_id: user_id.to_string(),
+        updated_at: chrono::Local::now().to_rfc3339(),
+        meta: meta.clone(),
+        messages: messages.iter().map(StoredMessage::from_message).collect(),
+    };
+    std::fs::write(&path, serde_json::to_string_pretty(&record)?)?;
+    Ok(())
+}
+
+/// List saved sessions for a user, newest first
+pub fn list_sessions(user_id: &str) -> Result<Vec<SessionSummary>> {
+    let Some(dir) = sessions_dir(user_id) else {
+        return Ok(vec![]);
+    };
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut sessions = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(record) = read_record(&path) else {
+            continue;
+        };
+        let preview = record
+            .messages
+            .iter()
+            .find(|m| m.role == MessageRole::User.as_str())
+            .map(|m| one_line_preview(&m.content, 60))
+            .unwrap_or_default();
+        sessions.push(SessionSummary {
+            session_id: record.session_id,
+            updated_at: record.updated_at,
+            preview,
+            meta: record.meta,
+        });
+    }
+
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(sessions)
+}
+
+/// Load a saved session's messages and metadata
+pub fn load_session(user_id: &str, session_id: &str) -> Result<LoadedSession> {
+    let path = session_path(user_id, session_id)
+        .ok_or_else(|| anyhow::anyhow!("no config directory available"))?;
+    if !path.exists() {
+        anyhow::bail!("no saved session {}", session_id);
+    }
+    let record = read_record(&path)?;
+    let saved_at = DateTime::parse_from_rfc3339(&record.updated_at)
+        .map(|timestamp| timestamp.with_timezone(&Local))
+        .unwrap_or_else(|_| Local::now());
+    Ok(LoadedSession {
+        messages: record
+            .messages
+            .into_iter()
+            .map(|stored| stored.into_message(saved_at))
+            .collect(),
+        meta: record.meta,
+    })
+}
+
+/// Move a renamed user's saved sessions to their new name. A session already
+/// saved under the new name stays where it is; returns how many stayed.
+pub fn move_user_sessions(old_user: &str, new_user: &str) -> Result<usize> {
+    let (Some(old_dir), Some(new_dir)) = (sessions_dir(old_user), sessions_dir(new_user)) else {
+        return Ok(0);
+    };
+    if !old_dir.exists() || old_dir == new_dir {
+        return Ok(0);
+    }
+    std::fs::create_dir_all(&new_dir)?;
+
+    let mut left_behind = 0;
+    for entry in std::fs::read_dir(&old_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(file_name) = path.file_name() else {
+            continue;
+        };
+        let target = new_dir.join(file_name);
+        if target.exists() {
+            left_behind += 1;
+            continue;
+        }
+        let mut record = read_record(&path)?;
+        record.user_id = new_user.to_string();
+        std::fs::write(&target, serde_json::to_string_pretty(&record)?)?;
+        std::fs::remove_file(&path)?;
+    }
+    // Only succeeds once nothing is left behind
+    let _ = std::fs::remove_dir(&old_dir);
+    Ok(left_behind)
+}
+
+fn read_record(path: &Path) -> Result<SessionRecord> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&text)?)
+}
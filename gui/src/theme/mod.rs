@@ -2,7 +2,20 @@
 //!
 //! A faithful implementation of the Monokai Pro color scheme.
 
-use gpui::Hsla;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use gpui::{ease_out_quint, pulsating_between, rgb, Animation, Hsla, Rgba};
+
+/// Accent fields that `/config <key> <hex>` can override
+pub const ACCENT_KEYS: [&str; 6] = [
+    "accent_red",
+    "accent_orange",
+    "accent_yellow",
+    "accent_green",
+    "accent_cyan",
+    "accent_purple",
+];
 
 /// Monokai Pro color palette
 pub mod colors {
@@ -125,4 +138,119 @@ impl MonokaiTheme {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Default palette with saved accent overrides (key → hex) applied
+    pub fn with_accents(overrides: &BTreeMap<String, String>) -> Self {
+        let mut theme = Self::default();
+        for (key, hex) in overrides {
+            if let Some(color) = parse_hex_color(hex) {
+                theme.set_accent(key, Some(color));
+            }
+        }
+        theme
+    }
+
+    fn accent_mut(&mut self, key: &str) -> Option<&mut Hsla> {
+        match key {
+            "accent_red" => Some(&mut self.accent_red),
+            "accent_orange" => Some(&mut self.accent_orange),
+            "accent_yellow" => Some(&mut self.accent_yellow),
+            "accent_green" => Some(&mut self.accent_green),
+            "accent_cyan" => Some(&mut self.accent_cyan),
+            "accent_purple" => Some(&mut self.accent_purple),
+            _ => None,
+        }
+    }
+
+    /// Override an accent, or restore the palette default with `None`.
+    /// Returns false for an unknown key.
+    pub fn set_accent(&mut self, key: &str, color: Option<Hsla>) -> bool {
+        let Some(default) = Self::default().accent_mut(key).copied() else {
+            return false;
+        };
+        match self.accent_mut(key) {
+            Some(slot) => {
+                *slot = color.unwrap_or(default);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Red (0.0) → yellow (0.5) → green (1.0), for scanning knowledge reliability
+    pub fn confidence_color(&self, confidence: f32) -> Hsla {
+        let t = confidence.clamp(0.0, 1.0);
+        if t < 0.5 {
+            lerp_color(self.accent_red, self.accent_yellow, t * 2.0)
+        } else {
+            lerp_color(self.accent_yellow, self.accent_green, (t - 0.5) * 2.0)
+        }
+    }
+
+    /// Stable accent for a user name, so each user keeps the same color
+    pub fn user_color(&self, name: &str) -> Hsla {
+        let accents = [
+            self.accent_red,
+            self.accent_orange,
+            self.accent_yellow,
+            self.accent_green,
+            self.accent_cyan,
+            self.accent_purple,
+        ];
+        // FNV-1a: unlike std's hasher, guaranteed not to change between builds
+        let hash = name.bytes().fold(0x811c9dc5u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        });
+        accents[hash as usize % accents.len()]
+    }
+}
+
+/// Blend in RGB so the red → yellow step doesn't sweep through blue hues
+fn lerp_color(from: Hsla, to: Hsla, t: f32) -> Hsla {
+    let (a, b) = (from.to_rgb(), to.to_rgb());
+    Rgba {
+        r: a.r + (b.r - a.r) * t,
+        g: a.g + (b.g - a.g) * t,
+        b: a.b + (b.b - a.b) * t,
+        a: a.a + (b.a - a.a) * t,
+    }
+    .into()
+}
+
+/// Parse `#rrggbb` or `#rgb` (the `#` is optional)
+pub fn parse_hex_color(text: &str) -> Option<Hsla> {
+    let hex = text.trim().trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = match hex.len() {
+        6 => u32::from_str_radix(hex, 16).ok()?,
+        // #abc → #aabbcc
+        3 => hex
+            .chars()
+            .try_fold(0u32, |acc, c| Some((acc << 8) | c.to_digit(16)? * 0x11))?,
+        _ => return None,
+    };
+    Some(rgb(value).into())
+}
+
+/// gpui-component draws the scrollbars and reads their colors from its own
+/// theme, so hand it ours
+pub fn apply_scrollbar_colors(theme: &MonokaiTheme, cx: &mut gpui::App) {
+    let component_theme = gpui_component::Theme::global_mut(cx);
+    component_theme.scrollbar = theme.scrollbar_bg;
+    component_theme.scrollbar_thumb = theme.scrollbar_thumb;
+    component_theme.scrollbar_thumb_hover = theme.scrollbar_thumb_hover;
+}
+
+/// Quick ease-out for content appearing when a panel or tool call expands
+pub fn expand_animation() -> Animation {
+    Animation::new(Duration::from_millis(150)).with_easing(ease_out_quint())
+}
+
+/// Subtle repeating pulse for in-progress indicators (use with `opacity`)
+pub fn pulse_animation() -> Animation {
+    Animation::new(Duration::from_millis(1200))
+        .repeat()
+        .with_easing(pulsating_between(0.4, 1.0))
 }
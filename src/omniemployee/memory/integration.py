@@ -74,11 +74,12 @@ class BIEMContextPlugin:
     
     # ==================== Context Integration ====================
     
-    async def prepare_context(self, current_input: str) -> str:
+    async def prepare_context(self, current_input: str, user_id: str = "") -> str:
         """Prepare memory context for injection into system prompt.
         
         Args:
             current_input: Current user input or task
+            user_id: User whose memories to use (current user if empty)
         
         Returns:
             Formatted memory context string
@@ -88,25 +89,28 @@ class BIEMContextPlugin:
         
         return await self.memory.get_context(
             current_input,
-            limit=self.config.max_memories_in_context
+            limit=self.config.max_memories_in_context,
+            user_id=user_id,
         )
     
     async def get_relevant_memories(
         self,
         query: str,
-        limit: int | None = None
+        limit: int | None = None,
+        user_id: str = ""
     ) -> list[MemoryNode]:
         """Get memories relevant to a query.
         
         Args:
             query: Search query
             limit: Maximum results
+            user_id: User whose memories to search (current user if empty)
         
         Returns:
             List of relevant MemoryNode objects
         """
         k = limit or self.config.max_memories_in_context
-        return await self.memory.recall(query, top_k=k)
+        return await self.memory.recall(query, top_k=k, user_id=user_id)
     
     # ==================== Recording ====================
     
@@ -36,6 +36,8 @@ class ProcessResult:
     """Result of processing a message for knowledge."""
     action: str = "none"  # "none", "stored", "conflict", "question"
     triples_stored: list[KnowledgeTriple] = field(default_factory=list)
+    # (old, new) pairs for stored triples that replaced an existing fact
+    triples_updated: list[tuple[KnowledgeTriple, KnowledgeTriple]] = field(default_factory=list)
     conflicts: list[ConflictResult] = field(default_factory=list)
     confirmation_prompts: list[str] = field(default_factory=list)
     pending_keys: list[str] = field(default_factory=list)
@@ -198,6 +200,7 @@ class KnowledgeLearningPlugin:
         
         # Process each triple
         stored = []
+        updated = []
         conflicts = []
         prompts = []
         pending_keys = []
@@ -217,6 +220,8 @@ class KnowledgeLearningPlugin:
                     if self._vector_store:
                         await self._vector_store.store(triple)
                     stored.append(triple)
+                    if conflict.existing_triple:
+                        updated.append((conflict.existing_triple, triple))
                 else:
                     # Add to pending confirmations for user-sourced conflicts
                     key = self._confirmation.add_pending(
@@ -247,6 +252,7 @@ class KnowledgeLearningPlugin:
         return ProcessResult(
             action=action,
             triples_stored=stored,
+            triples_updated=updated,
             conflicts=conflicts,
             confirmation_prompts=prompts,
             pending_keys=pending_keys,
@@ -8,8 +8,10 @@ A FastAPI app providing:
 from __future__ import annotations
 
 import asyncio
+import json
 import os
 import uuid
+from importlib.metadata import PackageNotFoundError, version as package_version
 from pathlib import Path
 from contextlib import asynccontextmanager
 from typing import Optional
@@ -40,6 +42,17 @@ from src.omniemployee.memory.knowledge import (
 class ChatRequest(BaseModel):
     message: str
     session_id: Optional[str] = None
+    system_prompt: Optional[str] = None
+
+
+class SeedMessage(BaseModel):
+    role: str
+    content: str
+
+
+class SeedRequest(BaseModel):
+    session_id: str
+    messages: list[SeedMessage]
 
 
 class ChatResponse(BaseModel):
@@ -269,6 +282,11 @@ if static_dir.exists():
 
 # ==================== Chat API ====================
 
+def _session_instructions(system_prompt: str) -> str:
+    """System prompt section for a client's per-session instructions."""
+    return f"## Session Instructions\n{system_prompt.strip()}"
+
+
 @app.post("/api/chat", response_model=ChatResponse)
 async def chat(request: ChatRequest):
     """Send a message to the agent and get a response."""
@@ -281,8 +299,10 @@ async def chat(request: ChatRequest):
     if session_id not in _sessions:
         _sessions[session_id] = {"messages": []}
     
-    # Build context from memory and knowledge
+    # Build context from session instructions, memory and knowledge
     context_parts = []
+    if request.system_prompt:
+        context_parts.append(_session_instructions(request.system_prompt))
     
     if _memory_plugin:
         memory_context = await _memory_plugin.prepare_context(request.message)
@@ -301,7 +321,7 @@ async def chat(request: ChatRequest):
     result = await _loop.run(request.message)
     
     # Clear memory context
-    if _memory_plugin or _knowledge_plugin:
+    if context_parts:
         _agent.context.clear_memory_context()
     
     # Extract tool calls from context
@@ -349,9 +369,15 @@ async def chat(request: ChatRequest):
 
 
 @app.get("/api/chat/stream")
-async def chat_stream(message: str, session_id: str = ""):
+async def chat_stream(
+    message: str, session_id: str = "", user_id: str = "", system_prompt: str = ""
+):
     """Stream chat response for real-time updates (SSE format).
     
+    `user_id` answers this one request with another user's memory context
+    without switching the current user. `system_prompt` adds per-session
+    instructions to the agent's system prompt for this request.
+    
     Events:
     - type: "context" - Memory and knowledge context used for this query (sent first)
     - type: "chunk" - Text content chunk
@@ -366,17 +392,20 @@ async def chat_stream(message: str, session_id: str = ""):
     session_id = session_id or str(uuid.uuid4())[:8]
     
     async def generate():
-        import json
         
         # Build context and track what was used
         context_parts = []
+        if system_prompt:
+            context_parts.append(_session_instructions(system_prompt))
         used_memories = []
         used_knowledge = []
         
         if _memory_plugin:
             try:
                 # Get actual memory nodes used
-                memories = await _memory_plugin.get_relevant_memories(message)
+                # The override applies to this lookup only; the global
+                # current user is left alone for concurrent requests
+                memories = await _memory_plugin.get_relevant_memories(message, user_id=user_id)
                 for node in memories:
                     used_memories.append({
                         "id": node.id,
@@ -384,7 +413,7 @@ async def chat_stream(message: str, session_id: str = ""):
                         "energy": node.energy,
                         "tier": node.tier.value if hasattr(node.tier, 'value') else str(node.tier),
                     })
-                memory_context = await _memory_plugin.prepare_context(message)
+                memory_context = await _memory_plugin.prepare_context(message, user_id=user_id)
                 if memory_context:
                     context_parts.append(memory_context)
             except Exception:
@@ -409,8 +438,12 @@ async def chat_stream(message: str, session_id: str = ""):
             except Exception:
                 pass
         
-        # Send context event first (what memory/knowledge was used for this query)
-        yield f"data: {json.dumps({'type': 'context', 'memories': used_memories, 'knowledge': used_knowledge})}\n\n"
+        # Send context event first (what memory/knowledge was used for this query).
+        # knowledge_limit lets the client tell a full list from a short one.
+        context_event = {'type': 'context', 'memories': used_memories, 'knowledge': used_knowledge}
+        if _knowledge_plugin:
+            context_event['knowledge_limit'] = _knowledge_plugin.config.max_context_items
+        yield f"data: {json.dumps(context_event)}\n\n"
         
         if context_parts:
             _agent.context.set_memory_context("\n\n".join(context_parts))
@@ -488,7 +521,7 @@ async def chat_stream(message: str, session_id: str = ""):
         yield f"data: {json.dumps({'type': 'done', 'tool_calls': tool_calls})}\n\n"
         
         # Cleanup and record
-        if _memory_plugin or _knowledge_plugin:
+        if context_parts:
             _agent.context.clear_memory_context()
         
         # Record to memory
@@ -504,9 +537,27 @@ async def chat_stream(message: str, session_id: str = ""):
         if _knowledge_plugin and _knowledge_plugin.is_available():
             try:
                 # Extract from user message
-                await _knowledge_plugin.process_message(message, role="user")
+                user_result = await _knowledge_plugin.process_message(message, role="user")
                 # Extract from agent response (search results, summaries)
-                await _knowledge_plugin.process_message(response_text, role="assistant")
+                agent_result = await _knowledge_plugin.process_message(response_text, role="assistant")
+                
+                # Let the client show what was learned (sent after 'done')
+                added, updated = [], []
+                for result in (user_result, agent_result):
+                    replaced = {id(new): old for old, new in result.triples_updated}
+                    for triple in result.triples_stored:
+                        change = {
+                            "subject": triple.subject,
+                            "predicate": triple.predicate,
+                            "object": triple.object,
+                        }
+                        if id(triple) in replaced:
+                            change["old_object"] = replaced[id(triple)].object
+                            updated.append(change)
+                        else:
+                            added.append(change)
+                if added or updated:
+                    yield f"data: {json.dumps({'type': 'knowledge_update', 'added': added, 'updated': updated})}\n\n"
             except Exception:
                 pass
     
@@ -534,6 +585,26 @@ async def clear_chat(session_id: str = ""):
     return {"status": "cleared"}
 
 
+@app.post("/api/chat/seed")
+async def seed_chat(request: SeedRequest):
+    """Replace the conversation context with the given messages (used when branching)."""
+    if not _agent:
+        raise HTTPException(status_code=503, detail="Agent not initialized")
+    
+    _agent.context.clear()
+    for message in request.messages:
+        if message.role == "user":
+            _agent.context.add_user_message(message.content)
+        elif message.role == "assistant":
+            _agent.context.add_assistant_message(message.content)
+    
+    _sessions[request.session_id] = {
+        "messages": [message.model_dump() for message in request.messages]
+    }
+    
+    return {"status": "seeded", "session_id": request.session_id}
+
+
 # ==================== Agent Info API ====================
 
 @app.get("/api/agent/info")
@@ -552,9 +623,18 @@ async def get_agent_info():
         "tools": stats.get("tools", []),
         "memory_enabled": _memory_plugin is not None,
         "knowledge_enabled": _knowledge_plugin is not None and _knowledge_plugin.is_available(),
+        "version": _backend_version(),
     }
 
 
+def _backend_version() -> str | None:
+    """Installed package version, or None when running from an uninstalled checkout."""
+    try:
+        return package_version("omniemployee")
+    except PackageNotFoundError:
+        return None
+
+
 # ==================== Memory APIs ====================
 
 @app.get("/", response_class=HTMLResponse)
@@ -933,6 +1013,147 @@ async def create_user(user_id: str):
     return {"success": True, "user_id": _current_user_id}
 
 
+async def _memory_user_exists(user_id: str) -> bool:
+    """Whether any memory tier holds nodes for user_id."""
+    if not _memory:
+        return False
+    if await _memory._l1.count(user_id=user_id):
+        return True
+    graph = _memory._l2_graph._graph
+    if any(graph.nodes[n].get("user_id") == user_id for n in graph.nodes()):
+        return True
+    if _memory._l2_vector._connected:
+        rows = _memory._l2_vector._client.query(
+            collection_name=_memory._l2_vector.config.collection_name,
+            filter=f"user_id == {json.dumps(user_id)}",
+            output_fields=["id"],
+            limit=1,
+        )
+        if rows:
+            return True
+    if _memory._tier._l3_available:
+        async with _memory._l3._pool.acquire() as conn:
+            if await conn.fetchval(
+                "SELECT 1 FROM crystal_facts WHERE user_id = $1 LIMIT 1", user_id
+            ):
+                return True
+    return False
+
+
+async def _migrate_memory_user(old_user_id: str, new_user_id: str) -> None:
+    """Move every memory node of old_user_id to new_user_id in all tiers.
+    
+    Raises on the first store that fails, so the caller can report it.
+    """
+    if not _memory:
+        return
+    
+    # L1 working memory
+    for node in await _memory._l1.list_all(user_id=old_user_id):
+        node.user_id = new_user_id
+    
+    # L2 graph
+    graph = _memory._l2_graph._graph
+    for node_id in graph.nodes():
+        if graph.nodes[node_id].get("user_id") == old_user_id:
+            graph.nodes[node_id]["user_id"] = new_user_id
+    
+    # L2 vector (Milvus) - upsert full rows with the new user_id; once
+    # rewritten they no longer match the filter, so page until none remain
+    if _memory._l2_vector._connected:
+        collection = _memory._l2_vector.config.collection_name
+        while True:
+            rows = _memory._l2_vector._client.query(
+                collection_name=collection,
+                filter=f"user_id == {json.dumps(old_user_id)}",
+                output_fields=["*"],
+                limit=1000,
+            )
+            if not rows:
+                break
+            for row in rows:
+                row["user_id"] = new_user_id
+            _memory._l2_vector._client.upsert(collection_name=collection, data=rows)
+    
+    # L3 crystal facts and links
+    if _memory._tier._l3_available:
+        async with _memory._l3._pool.acquire() as conn:
+            async with conn.transaction():
+                await conn.execute(
+                    "UPDATE crystal_facts SET user_id = $1 WHERE user_id = $2",
+                    new_user_id,
+                    old_user_id,
+                )
+                await conn.execute(
+                    "UPDATE crystal_links SET user_id = $1 WHERE user_id = $2",
+                    new_user_id,
+                    old_user_id,
+                )
+
+
+@app.post("/api/user/rename")
+async def rename_user(old_user_id: str, new_user_id: str):
+    """Rename a user and make the new name current.
+    
+    Memory nodes (all tiers) and knowledge attribution move to the new name.
+    Names already used by either memory or knowledge are rejected.
+    """
+    global _current_user_id, _knowledge_plugin, _memory
+    
+    new_user_id = (new_user_id or "").strip()
+    if not new_user_id:
+        return {"success": False, "error": "Invalid user_id"}
+    if new_user_id == old_user_id:
+        return {"success": False, "error": "New name is the same as the old one"}
+    
+    try:
+        taken = await _memory_user_exists(new_user_id)
+        if not taken and _knowledge_store:
+            async with _knowledge_store._pool.acquire() as conn:
+                taken = bool(await conn.fetchval(
+                    "SELECT 1 FROM knowledge_triples WHERE user_id = $1 LIMIT 1",
+                    new_user_id,
+                ))
+    except Exception as e:
+        return {"success": False, "error": f"Could not check whether {new_user_id} exists: {e}"}
+    if taken:
+        return {"success": False, "error": f"User {new_user_id} already exists"}
+    
+    # Memory first: it is the per-user data, so never rename without it
+    try:
+        await _migrate_memory_user(old_user_id, new_user_id)
+    except Exception as e:
+        return {
+            "success": False,
+            "error": f"Could not move memories to {new_user_id}: {e}. "
+            f"Some may already be moved; renaming back to {old_user_id} restores them.",
+        }
+    
+    if _knowledge_store:
+        try:
+            async with _knowledge_store._pool.acquire() as conn:
+                await conn.execute(
+                    "UPDATE knowledge_triples SET user_id = $1 WHERE user_id = $2",
+                    new_user_id,
+                    old_user_id,
+                )
+        except Exception as e:
+            return {
+                "success": False,
+                "error": f"Memories moved to {new_user_id}, but knowledge attribution "
+                f"could not be updated: {e}",
+            }
+    
+    if get_current_user_id() == old_user_id:
+        _current_user_id = new_user_id
+        if _memory:
+            _memory.set_user_id(new_user_id)
+        if _knowledge_plugin:
+            _knowledge_plugin.config.user_id = new_user_id
+    
+    return {"success": True, "user_id": new_user_id}
+
+
 @app.get("/api/debug/user_ids")
 async def debug_user_ids():
     """Debug endpoint to show all user_ids in the system."""